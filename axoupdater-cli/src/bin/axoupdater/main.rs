@@ -1,7 +1,20 @@
+use std::io::{IsTerminal, Write};
+
 use axocli::{CliApp, CliAppBuilder};
-use axoupdater::AxoUpdater;
-use clap::Parser;
-use miette::miette;
+use axoupdater::{AxoUpdater, NoUpdateReason, UpdateNeeded, UpdateOutcome};
+use clap::{Parser, ValueEnum};
+use miette::{miette, IntoDiagnostic};
+use serde_json::json;
+
+/// How to report the result of the update
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable messages on stderr
+    #[default]
+    Human,
+    /// A JSON object on stdout
+    Json,
+}
 
 #[derive(Parser)]
 struct CliArgs {
@@ -16,9 +29,35 @@ struct CliArgs {
     /// Allows prereleases when just updating to "latest"
     #[clap(long)]
     prerelease: bool,
+
+    /// How to report the result of the update
+    #[clap(long, value_enum, default_value_t = OutputFormat::Human)]
+    output: OutputFormat,
+
+    /// Updates without asking for confirmation. Confirmation is only asked
+    /// for when run interactively; otherwise, updates proceed regardless.
+    #[clap(short, long)]
+    yes: bool,
+
+    /// Shows the new release's notes when asking for confirmation
+    #[clap(long)]
+    show_notes: bool,
+
+    /// Checks for an update without installing it. Exits with status 0 if
+    /// already up to date, 10 if an update is available, or 11 if this copy
+    /// wasn't installed by its installer and can't update itself.
+    #[clap(long)]
+    check: bool,
 }
 
-fn real_main(cli: &CliApp<CliArgs>) -> Result<(), miette::Report> {
+/// The exit status used by `--check` when an update is available
+const UPDATE_AVAILABLE_EXIT_CODE: i32 = 10;
+
+/// The exit status used by `--check` when this copy can't update itself
+const NOT_ELIGIBLE_EXIT_CODE: i32 = 11;
+
+/// Runs the updater, returning the status to exit with.
+fn real_main(cli: &CliApp<CliArgs>) -> Result<i32, miette::Report> {
     if cli.config.tag.is_some() && cli.config.version.is_some() {
         return Err(miette!(
             "Both `tag` and `version` are specified; these options are mutually exclusive!"
@@ -49,15 +88,183 @@ fn real_main(cli: &CliApp<CliArgs>) -> Result<(), miette::Report> {
     };
     updater.configure_version_specifier(specifier);
 
-    if let Some(result) = updater.run_sync()? {
-        eprintln!("New release {} installed!", result.new_version)
+    if cli.config.check {
+        return check(&mut updater, cli.config.output);
+    }
+
+    if cli.config.output == OutputFormat::Json {
+        // Keep stdout clear for our own output
+        updater.disable_installer_stdout();
+    }
+
+    if !cli.config.yes
+        && std::io::stdin().is_terminal()
+        && !confirm_update(&mut updater, cli.config.show_notes)?
+    {
+        if cli.config.output == OutputFormat::Json {
+            println!("{}", json!({ "updated": false, "reason": "declined" }));
+        } else {
+            eprintln!("Not upgrading");
+        }
+        return Ok(0);
+    }
+
+    let outcome = updater.run_sync()?;
+
+    if cli.config.output == OutputFormat::Json {
+        println!("{}", outcome_json(&outcome));
+        return Ok(0);
+    }
+
+    match outcome {
+        UpdateOutcome::Updated(result) => match updater.primary_binary() {
+            Some(binary) => eprintln!("New release {} of {binary} installed!", result.new_version),
+            None => eprintln!("New release {} installed!", result.new_version),
+        },
+        UpdateOutcome::Skipped(NoUpdateReason::UpToDate) => {
+            eprintln!("Already up to date; not upgrading");
+        }
+        UpdateOutcome::Skipped(NoUpdateReason::NotEligible(_)) => {
+            eprintln!("This copy wasn't installed by its installer; not upgrading");
+        }
+        UpdateOutcome::Skipped(NoUpdateReason::NoReleaseFound) => {
+            eprintln!("No matching releases were found; not upgrading");
+        }
+    }
+
+    Ok(0)
+}
+
+/// Reports whether an update is available without installing it. Returns
+/// `UPDATE_AVAILABLE_EXIT_CODE` if there is one, or `NOT_ELIGIBLE_EXIT_CODE`
+/// if this copy can't be updated.
+fn check(updater: &mut AxoUpdater, output: OutputFormat) -> Result<i32, miette::Report> {
+    let needed = updater.is_update_needed_detailed_sync()?;
+    // Checking eligibility doesn't need the release, so it may not have been
+    // fetched yet
+    if updater.requested_release().is_none() {
+        updater.query_new_version_sync()?;
+    }
+    let available_version = updater
+        .requested_release()
+        .map(|release| release.version.to_string());
+    let current_version = installed_version(updater);
+    let update_available = needed == UpdateNeeded::UpdateAvailable;
+    let eligible = !matches!(needed, UpdateNeeded::NotEligible(_));
+
+    if output == OutputFormat::Json {
+        println!(
+            "{}",
+            json!({
+                "update_available": update_available,
+                "eligible": eligible,
+                "current_version": current_version,
+                "available_version": available_version,
+            })
+        );
     } else {
-        eprintln!("Already up to date; not upgrading");
+        let unknown = || "unknown".to_owned();
+        eprintln!(
+            "Current version: {}",
+            current_version.unwrap_or_else(unknown)
+        );
+        eprintln!(
+            "Available version: {}",
+            available_version.unwrap_or_else(unknown)
+        );
+        match needed {
+            UpdateNeeded::UpdateAvailable => eprintln!("An update is available"),
+            UpdateNeeded::UpToDate => eprintln!("Already up to date"),
+            UpdateNeeded::NotEligible(_) => {
+                eprintln!("This copy wasn't installed by its installer, so it can't update itself")
+            }
+        }
     }
 
-    Ok(())
+    Ok(match needed {
+        UpdateNeeded::UpdateAvailable => UPDATE_AVAILABLE_EXIT_CODE,
+        UpdateNeeded::UpToDate => 0,
+        UpdateNeeded::NotEligible(_) => NOT_ELIGIBLE_EXIT_CODE,
+    })
+}
+
+/// Returns the installed version, as recorded in the install receipt.
+fn installed_version(updater: &AxoUpdater) -> Option<String> {
+    updater.current_version().map(|version| version.to_string())
+}
+
+/// Asks the user whether to install the available update. Returns true
+/// without asking if there's no update to install.
+fn confirm_update(updater: &mut AxoUpdater, show_notes: bool) -> Result<bool, miette::Report> {
+    if !updater.is_update_needed_sync()? {
+        return Ok(true);
+    }
+    // Forced updates don't need to look at the release to decide, so it may
+    // not have been fetched yet
+    if updater.requested_release().is_none() {
+        updater.query_new_version_sync()?;
+    }
+    let Some(release) = updater.requested_release() else {
+        return Ok(true);
+    };
+
+    let app_name = updater.name.clone().unwrap_or_default();
+    let current_version =
+        installed_version(updater).unwrap_or_else(|| "an unknown version".to_owned());
+
+    if show_notes {
+        if let Some(notes) = release
+            .body
+            .as_deref()
+            .filter(|notes| !notes.trim().is_empty())
+        {
+            eprintln!("{}\n", notes.trim());
+        }
+    }
+
+    eprint!(
+        "Update {app_name} from {current_version} to {}? [y/N] ",
+        release.version
+    );
+    std::io::stderr().flush().into_diagnostic()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).into_diagnostic()?;
+
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Describes the result of the update for `--output json`.
+fn outcome_json(outcome: &UpdateOutcome) -> serde_json::Value {
+    match outcome {
+        UpdateOutcome::Updated(result) => json!({
+            "updated": true,
+            "old_version": result.old_version.as_ref().map(|v| v.to_string()),
+            "new_version": result.new_version.to_string(),
+            "tag": result.new_version_tag,
+            "install_prefix": result.install_prefix.as_str(),
+        }),
+        UpdateOutcome::Skipped(reason) => {
+            let reason = match reason {
+                NoUpdateReason::UpToDate => "up_to_date",
+                NoUpdateReason::NotEligible(_) => "not_eligible",
+                NoUpdateReason::NoReleaseFound => "no_release_found",
+            };
+            json!({ "updated": false, "reason": reason })
+        }
+    }
 }
 
 fn main() {
-    CliAppBuilder::new("axoupdater").start(CliArgs::parse(), real_main);
+    CliAppBuilder::new("axoupdater").start(CliArgs::parse(), |cli| {
+        let status = real_main(cli)?;
+        // Only exit here, once everything real_main set up has been cleaned up
+        if status != 0 {
+            std::process::exit(status);
+        }
+
+        Ok(())
+    });
 }
@@ -2,32 +2,15 @@ use std::env::consts::EXE_SUFFIX;
 
 use axoasset::LocalAsset;
 use axoprocess::Cmd;
+use axoupdater::test::extract_archive;
 use camino::{Utf8Path, Utf8PathBuf};
 use tempfile::TempDir;
 
 static BIN: &str = env!("CARGO_BIN_EXE_axoupdater");
 static RECEIPT_TEMPLATE: &str = r#"{"binaries":["axolotlsay"],"install_prefix":"INSTALL_PREFIX","provider":{"source":"cargo-dist","version":"CARGO_DIST_VERSION"},"source":{"app_name":"axolotlsay","name":"cargodisttest","owner":"mistydemeo","release_type":"github"},"version":"VERSION"}"#;
 
-// Handle aarch64 later
 fn triple() -> String {
-    match std::env::consts::OS {
-        "windows" => "x86_64-pc-windows-msvc".to_owned(),
-        "macos" => {
-            if std::env::consts::ARCH == "x86_64" {
-                "x86_64-apple-darwin".to_owned()
-            } else {
-                "aarch64-apple-darwin".to_owned()
-            }
-        }
-        "linux" => {
-            if std::env::consts::ARCH == "x86_64" {
-                "x86_64-unknown-linux-gnu".to_owned()
-            } else {
-                "aarch64-unknown-linux-gnu".to_owned()
-            }
-        }
-        _ => unimplemented!(),
-    }
+    axoupdater::target_triple().expect("no cargo-dist target for this platform")
 }
 
 fn axolotlsay_tarball_path(version: &str) -> String {
@@ -105,7 +88,7 @@ fn test_upgrade() -> std::io::Result<()> {
         &bindir.to_path_buf(),
     )?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
@@ -170,7 +153,7 @@ fn test_upgrade_xdg_config_home() -> std::io::Result<()> {
         &bindir.to_path_buf(),
     )?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
@@ -233,7 +216,7 @@ fn test_upgrade_allow_prerelease() -> std::io::Result<()> {
         &bindir.to_path_buf(),
     )?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
@@ -300,7 +283,7 @@ fn test_upgrade_to_specific_version() -> std::io::Result<()> {
         &bindir.to_path_buf(),
     )?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
@@ -359,7 +342,7 @@ fn test_downgrade_to_specific_version() -> std::io::Result<()> {
         &bindir.to_path_buf(),
     )?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
@@ -431,7 +414,7 @@ fn test_downgrade_to_specific_old_version() -> std::io::Result<()> {
         &bindir.to_path_buf(),
     )?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
@@ -492,7 +475,7 @@ fn test_upgrade_from_prefix_with_no_bin() -> std::io::Result<()> {
     // /bin bug mentioned above
     write_receipt(base_version, "0.15.0", &prefix, &prefix)?;
 
-    LocalAsset::untar_gz_all(&compressed_path, bindir).unwrap();
+    extract_archive(&compressed_path, bindir).unwrap();
 
     // Now install our copy of the updater instead of the one axolotlsay came with
     let updater_path = bindir.join(format!("axolotlsay-update{EXE_SUFFIX}"));
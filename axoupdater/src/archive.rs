@@ -0,0 +1,382 @@
+//! Installing from cargo-dist's archives instead of its installers
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use tempfile::TempDir;
+
+use crate::{
+    errors::*, find_installed_binary, native_triple, target_triple, Asset, AxoUpdater, Release,
+    UpdateOutcome, UpdateResult,
+};
+
+impl AxoUpdater {
+    /// Installs `release` by downloading the archive built for this
+    /// platform and copying its binaries into `install_prefix`.
+    pub(crate) async fn install_from_archive(
+        &self,
+        release: &Release,
+        install_prefix: &Utf8Path,
+        tempdir: &TempDir,
+    ) -> AxoupdateResult<UpdateOutcome> {
+        let app_name = self.name.clone().unwrap_or_default();
+        let Some(archive) = self.select_archive_asset(release, &app_name) else {
+            return Err(AxoupdateError::NoInstallerForPackage {});
+        };
+
+        let extracted = self.download_archive(release, archive, tempdir).await?;
+
+        self.install_extracted(release, &extracted, install_prefix)
+    }
+
+    /// Installs the binaries from `release`'s archive, already extracted to
+    /// `extracted`, into `install_prefix`, running the update hooks around
+    /// it.
+    fn install_extracted(
+        &self,
+        release: &Release,
+        extracted: &Utf8Path,
+        install_prefix: &Utf8Path,
+    ) -> AxoupdateResult<UpdateOutcome> {
+        let result = UpdateResult::for_release(
+            self.current_version.clone(),
+            release,
+            install_prefix.to_owned(),
+        );
+
+        if let Some(hook) = &self.pre_update_hook {
+            hook(&result).map_err(|message| AxoupdateError::HookAborted { message })?;
+        }
+
+        // As with installers, once binaries start being replaced, they're
+        // allowed to finish
+        self.check_cancelled()?;
+
+        install_binaries(extracted, install_prefix, &self.binaries)?;
+
+        self.verify_installed_update(&result.new_version)?;
+
+        if let Some(hook) = &self.post_update_hook {
+            hook(&result).map_err(|message| AxoupdateError::PostUpdateHookFailed { message })?;
+        }
+
+        Ok(UpdateOutcome::Updated(result))
+    }
+
+    /// Downloads and verifies `archive`, one of `release`'s assets, and
+    /// extracts it into `tempdir`. Returns the directory it was extracted to.
+    pub(crate) async fn download_archive(
+        &self,
+        release: &Release,
+        archive: &Asset,
+        tempdir: &TempDir,
+    ) -> AxoupdateResult<Utf8PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(url = %archive.browser_download_url, "selected archive");
+
+        let tempdir = Utf8Path::from_path(tempdir.path()).ok_or_else(|| {
+            AxoupdateError::CaminoConversionFailed {
+                path: tempdir.path().to_path_buf(),
+            }
+        })?;
+        let archive_path = tempdir.join(&archive.name);
+
+        let client = self.http_client()?;
+        self.download_asset_to(&client, archive, archive_path.as_std_path())
+            .await?;
+
+        self.verify_installer_checksum(&client, release, archive, &archive_path)
+            .await?;
+        self.verify_installer_signature(&client, release, archive, &archive_path)
+            .await?;
+        let extracted = tempdir.join("extracted");
+        LocalAsset::create_dir_all(&extracted)?;
+        extract_archive(&archive_path, &extracted)?;
+
+        Ok(extracted)
+    }
+
+    /// Selects the archive for this platform from `release`'s assets, if
+    /// any. As with installers, if `prefer_native_arch` is set, an archive
+    /// for this machine's native architecture is preferred when running
+    /// under emulation.
+    pub(crate) fn select_archive_asset<'a>(
+        &self,
+        release: &'a Release,
+        app_name: &str,
+    ) -> Option<&'a Asset> {
+        let native = self.prefer_native_arch.then(native_triple).flatten();
+        native
+            .into_iter()
+            .chain(target_triple())
+            .find_map(|triple| find_archive(release, app_name, &triple))
+    }
+}
+
+/// The compression formats cargo-dist may use for its archives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// A gzip-compressed tarball
+    TarGz,
+    /// An xz-compressed tarball
+    TarXz,
+    /// A zstd-compressed tarball
+    TarZstd,
+    /// A zip file
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Determines an archive's format from its filename, if possible.
+    pub fn from_path(path: &Utf8Path) -> Option<Self> {
+        let name = path.file_name()?;
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Some(Self::TarXz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::TarZstd)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extracts the entire contents of the archive at `path` into `dest`,
+/// choosing a decompressor based on the archive's file extension.
+pub fn extract_archive(path: &Utf8Path, dest: &Utf8Path) -> AxoupdateResult<()> {
+    let Some(format) = ArchiveFormat::from_path(path) else {
+        return Err(AxoupdateError::UnknownArchiveFormat {
+            path: path.to_owned(),
+        });
+    };
+
+    match format {
+        ArchiveFormat::TarGz => LocalAsset::untar_gz_all(path, dest)?,
+        ArchiveFormat::TarXz => LocalAsset::untar_xz_all(path, dest)?,
+        ArchiveFormat::TarZstd => LocalAsset::untar_zstd_all(path, dest)?,
+        ArchiveFormat::Zip => LocalAsset::unzip_all(path, dest)?,
+    }
+
+    Ok(())
+}
+
+/// Finds the archive built for `triple` among `release`'s assets.
+pub(crate) fn find_archive<'a>(
+    release: &'a Release,
+    app_name: &str,
+    triple: &str,
+) -> Option<&'a Asset> {
+    release
+        .find_assets(&format!("{app_name}-{triple}.*"))
+        .into_iter()
+        .find(|asset| ArchiveFormat::from_path(Utf8Path::new(&asset.name)).is_some())
+}
+
+/// Copies each of `binaries` from the extracted archive at `extracted`
+/// into the install prefix `root`. Binaries replace existing copies
+/// wherever they are in the prefix; new binaries go in its `bin` directory.
+pub(crate) fn install_binaries(
+    extracted: &Utf8Path,
+    root: &Utf8Path,
+    binaries: &[String],
+) -> AxoupdateResult<()> {
+    let ourselves = std::env::current_exe()
+        .and_then(|path| path.canonicalize())
+        .ok();
+
+    for binary in binaries {
+        let filename = if cfg!(windows) && !binary.ends_with(".exe") {
+            format!("{binary}.exe")
+        } else {
+            binary.to_owned()
+        };
+        let Some(source) = find_file(extracted, &filename)? else {
+            return Err(AxoupdateError::BinaryNotInArchive {
+                binary: binary.to_owned(),
+            });
+        };
+        let dest =
+            find_installed_binary(root, binary).unwrap_or_else(|| root.join("bin").join(&filename));
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%source, %dest, "installing binary");
+
+        let is_ourselves =
+            ourselves.is_some() && dest.canonicalize().ok().as_deref() == ourselves.as_deref();
+        replace_binary(&source, &dest, is_ourselves)?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the binary at `dest` with a copy of `source`. `is_ourselves`
+/// indicates that `dest` is the running executable.
+pub(crate) fn replace_binary(
+    source: &Utf8Path,
+    dest: &Utf8Path,
+    is_ourselves: bool,
+) -> AxoupdateResult<()> {
+    // Stage the new binary next to its destination and then move it into
+    // place, so that `dest` is never left partially written. This also
+    // works when `dest` is running, since it's never written to directly.
+    let staged = Utf8PathBuf::from(format!("{dest}.new"));
+    if let Some(parent) = dest.parent() {
+        LocalAsset::create_dir_all(parent)?;
+    }
+    std::fs::copy(source, &staged)?;
+
+    // Windows won't let us move over the running executable, either
+    if cfg!(windows) && is_ourselves {
+        #[cfg(windows)]
+        {
+            self_replace::self_replace(&staged)?;
+            std::fs::remove_file(&staged)?;
+        }
+    } else {
+        std::fs::rename(&staged, dest)?;
+    }
+
+    Ok(())
+}
+
+/// Recursively searches `dir` for a file named `name`. cargo-dist's
+/// archives may place binaries at their root or in a subdirectory.
+pub(crate) fn find_file(dir: &Utf8Path, name: &str) -> AxoupdateResult<Option<Utf8PathBuf>> {
+    for entry in dir.read_dir_utf8()? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(path, name)? {
+                return Ok(Some(found));
+            }
+        } else if entry.file_name() == name {
+            return Ok(Some(path.to_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_archive, install_binaries, replace_binary, ArchiveFormat};
+    use crate::test::fixtures::{asset, release, release_with_assets};
+    use crate::{AxoUpdater, AxoupdateError};
+    use camino::Utf8Path;
+
+    #[test]
+    fn test_archive_format_from_path() {
+        let format = |p: &str| ArchiveFormat::from_path(Utf8Path::new(p));
+
+        assert_eq!(
+            format("axolotlsay-x86_64-unknown-linux-gnu.tar.gz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            format("/tmp/axolotlsay-aarch64-apple-darwin.tar.xz"),
+            Some(ArchiveFormat::TarXz)
+        );
+        assert_eq!(
+            format("axolotlsay-x86_64-unknown-linux-musl.tar.zst"),
+            Some(ArchiveFormat::TarZstd)
+        );
+        assert_eq!(
+            format("axolotlsay-x86_64-pc-windows-msvc.zip"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(format("axolotlsay-installer.sh"), None);
+    }
+
+    #[test]
+    fn test_find_archive() {
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                asset("axolotlsay-installer.sh"),
+                asset("axolotlsay-x86_64-unknown-linux-gnu.tar.gz.sha256"),
+                asset("axolotlsay-x86_64-unknown-linux-gnu.tar.gz"),
+                asset("axolotlsay-x86_64-pc-windows-msvc.zip"),
+            ],
+        );
+        let find =
+            |triple: &str| find_archive(&release, "axolotlsay", triple).map(|a| a.name.as_str());
+
+        assert_eq!(
+            find("x86_64-unknown-linux-gnu"),
+            Some("axolotlsay-x86_64-unknown-linux-gnu.tar.gz")
+        );
+        assert_eq!(
+            find("x86_64-pc-windows-msvc"),
+            Some("axolotlsay-x86_64-pc-windows-msvc.zip")
+        );
+        assert_eq!(find("aarch64-apple-darwin"), None);
+    }
+
+    #[test]
+    fn test_install_binaries() {
+        let exe = |name: &str| format!("{name}{}", std::env::consts::EXE_SUFFIX);
+        let extracted = tempfile::TempDir::new().unwrap();
+        let extracted = Utf8Path::from_path(extracted.path()).unwrap();
+        let archive_root = extracted.join("axolotlsay-x86_64-unknown-linux-gnu");
+        std::fs::create_dir_all(&archive_root).unwrap();
+        std::fs::write(archive_root.join(exe("axolotlsay")), "new").unwrap();
+
+        let prefix = tempfile::TempDir::new().unwrap();
+        let prefix = Utf8Path::from_path(prefix.path()).unwrap();
+        // An existing binary at the root of the prefix is replaced in place
+        std::fs::write(prefix.join(exe("axolotlsay")), "old").unwrap();
+
+        install_binaries(extracted, prefix, &["axolotlsay".to_owned()]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(prefix.join(exe("axolotlsay"))).unwrap(),
+            "new"
+        );
+        assert!(!prefix.join("bin").join(exe("axolotlsay")).exists());
+
+        let missing = install_binaries(extracted, prefix, &["other".to_owned()]);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_install_extracted_cancelled() {
+        let exe = |name: &str| format!("{name}{}", std::env::consts::EXE_SUFFIX);
+        let extracted = tempfile::TempDir::new().unwrap();
+        let extracted = Utf8Path::from_path(extracted.path()).unwrap();
+        std::fs::write(extracted.join(exe("axolotlsay")), "new").unwrap();
+        let prefix = tempfile::TempDir::new().unwrap();
+        let prefix = Utf8Path::from_path(prefix.path()).unwrap();
+        std::fs::write(prefix.join(exe("axolotlsay")), "old").unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.binaries = vec!["axolotlsay".to_owned()];
+        // Cancellation requested after the archive was downloaded and
+        // extracted still stops the update
+        updater.set_cancellation_token(futures_util::future::ready(()));
+
+        assert!(matches!(
+            updater.install_extracted(&release("1.0.0"), extracted, prefix),
+            Err(AxoupdateError::Cancelled {})
+        ));
+        assert_eq!(
+            std::fs::read_to_string(prefix.join(exe("axolotlsay"))).unwrap(),
+            "old"
+        );
+    }
+
+    #[test]
+    fn test_replace_binary() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        let source = root.join("new-axoupdater");
+        let dest = root.join("axolotlsay-update");
+        std::fs::write(&source, "new").unwrap();
+        std::fs::write(&dest, "old").unwrap();
+
+        replace_binary(&source, &dest, false).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "new");
+        assert!(source.exists());
+        assert!(!root.join("axolotlsay-update.new").exists());
+    }
+}
@@ -0,0 +1,296 @@
+//! Checking for updates to several apps at once
+
+use crate::{
+    errors::*, latest_stable_of, AxoUpdater, Release, ReleaseSource, ReleaseSourceType,
+    SkippedTags, Version,
+};
+
+/// An app whose updates are checked as part of a `BatchUpdater`
+struct BatchApp {
+    app_name: String,
+    source: ReleaseSource,
+    current_version: Version,
+}
+
+/// Checks for updates to several apps at once.
+///
+/// Apps whose releases are hosted in the same repository share a single
+/// fetch of that repository's releases, and every request shares one HTTP
+/// client. For suites of tools released together, this uses far fewer API
+/// requests than checking each app with its own `AxoUpdater`.
+pub struct BatchUpdater {
+    apps: Vec<BatchApp>,
+    /// Supplies the HTTP client configuration and authorization tokens
+    updater: AxoUpdater,
+}
+
+impl Default for BatchUpdater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchUpdater {
+    /// Creates a new BatchUpdater without any apps to check.
+    pub fn new() -> BatchUpdater {
+        BatchUpdater {
+            apps: vec![],
+            updater: AxoUpdater::new(),
+        }
+    }
+
+    /// Adds an app to check for updates. `current_version` is the version
+    /// of the app that's installed.
+    pub fn add_app(
+        &mut self,
+        app_name: &str,
+        source: ReleaseSource,
+        current_version: Version,
+    ) -> &mut BatchUpdater {
+        self.apps.push(BatchApp {
+            app_name: app_name.to_owned(),
+            source,
+            current_version,
+        });
+
+        self
+    }
+
+    /// Configures the GitHub token used to fetch releases; see
+    /// `AxoUpdater::set_github_token`.
+    pub fn set_github_token(&mut self, token: &str) -> &mut BatchUpdater {
+        self.updater.set_github_token(token);
+
+        self
+    }
+
+    /// Configures the Axo Releases token used to fetch releases; see
+    /// `AxoUpdater::set_axo_token`.
+    pub fn set_axo_token(&mut self, token: &str) -> &mut BatchUpdater {
+        self.updater.set_axo_token(token);
+
+        self
+    }
+
+    /// Checks each app for updates, returning whether each app's latest
+    /// stable release is newer than its installed version. Results are
+    /// returned in the order the apps were added. If fetching a
+    /// repository's releases fails, every app released from it reports
+    /// the failure.
+    pub async fn check(&self) -> Vec<(String, AxoupdateResult<bool>)> {
+        let mut results: Vec<Option<AxoupdateResult<bool>>> =
+            self.apps.iter().map(|_| None).collect();
+
+        for group in self.group_by_repository() {
+            let source = &self.apps[group[0]].source;
+            match self.fetch_releases(source, &group).await {
+                Ok(releases) => {
+                    for (index, releases) in group.iter().zip(releases) {
+                        let app = &self.apps[*index];
+                        results[*index] = Some(update_needed(app, releases));
+                    }
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    for index in &group {
+                        results[*index] = Some(Err(AxoupdateError::BatchFetchFailed {
+                            owner: source.owner.clone(),
+                            name: source.name.clone(),
+                            message: message.clone(),
+                        }));
+                    }
+                }
+            }
+        }
+
+        self.apps
+            .iter()
+            .zip(results)
+            .map(|(app, result)| {
+                let result = result.expect("every app belongs to a repository");
+                (app.app_name.clone(), result)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "blocking")]
+    /// Identical to BatchUpdater::check(), but performed synchronously.
+    /// If this is called from within a tokio runtime, every app reports
+    /// `AxoupdateError::BlockingInAsyncContext`.
+    pub fn check_sync(&self) -> Vec<(String, AxoupdateResult<bool>)> {
+        crate::block_on(self.check()).unwrap_or_else(|_| {
+            self.apps
+                .iter()
+                .map(|app| {
+                    let result = Err(AxoupdateError::BlockingInAsyncContext {});
+                    (app.app_name.clone(), result)
+                })
+                .collect()
+        })
+    }
+
+    /// Groups the apps' indices by the repository their releases are
+    /// hosted in, preserving the order they were added in.
+    fn group_by_repository(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<(String, Vec<usize>)> = vec![];
+        for (index, app) in self.apps.iter().enumerate() {
+            let key = format!(
+                "{}:{}/{}",
+                app.source.release_type, app.source.owner, app.source.name
+            );
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, indices)) => indices.push(index),
+                None => groups.push((key, vec![index])),
+            }
+        }
+
+        groups.into_iter().map(|(_, indices)| indices).collect()
+    }
+
+    /// Fetches the releases of each of the apps at `indices`, which share
+    /// the repository `source`, returning them in the same order.
+    #[cfg_attr(
+        not(all(feature = "github_releases", feature = "axo_releases")),
+        allow(unused_variables)
+    )]
+    async fn fetch_releases(
+        &self,
+        source: &ReleaseSource,
+        indices: &[usize],
+    ) -> AxoupdateResult<Vec<Vec<Release>>> {
+        let skipped_tags = SkippedTags::default();
+        match source.release_type {
+            #[cfg(feature = "github_releases")]
+            ReleaseSourceType::GitHub => {
+                use crate::release::github::GithubRequest;
+
+                let client = self.updater.http_client()?;
+                let raw = crate::release::github::get_raw_github_releases(GithubRequest {
+                    token: &self.updater.tokens.github,
+                    ..GithubRequest::new(&client, &source.owner, &source.name, &source.app_name)
+                })
+                .await?;
+
+                Ok(indices
+                    .iter()
+                    .map(|index| {
+                        crate::release::github::releases_for_app(
+                            &self.apps[*index].source.app_name,
+                            None,
+                            raw.clone(),
+                            &skipped_tags,
+                        )
+                    })
+                    .collect())
+            }
+            #[cfg(not(feature = "github_releases"))]
+            ReleaseSourceType::GitHub => Err(AxoupdateError::BackendDisabled {
+                backend: "github".to_owned(),
+            }),
+            #[cfg(feature = "axo_releases")]
+            ReleaseSourceType::Axo => {
+                let app_names: Vec<String> = indices
+                    .iter()
+                    .map(|index| self.apps[*index].source.app_name.clone())
+                    .collect();
+                let mut releases = crate::release::axodotdev::get_axo_releases_many(
+                    &source.owner,
+                    &app_names,
+                    &self.updater.tokens.axodotdev,
+                    &skipped_tags,
+                )
+                .await?;
+
+                Ok(app_names
+                    .iter()
+                    .map(|app_name| releases.remove(app_name).unwrap_or_default())
+                    .collect())
+            }
+            #[cfg(not(feature = "axo_releases"))]
+            ReleaseSourceType::Axo => Err(AxoupdateError::BackendDisabled {
+                backend: "axodotdev".to_owned(),
+            }),
+        }
+    }
+}
+
+/// Determines whether `releases` contains a stable release newer than the
+/// version of `app` that's installed.
+fn update_needed(app: &BatchApp, releases: Vec<Release>) -> AxoupdateResult<bool> {
+    let Some(latest) = latest_stable_of(releases) else {
+        return Err(AxoupdateError::NoStableReleases {
+            app_name: app.app_name.clone(),
+            status: None,
+            message: None,
+        });
+    };
+
+    Ok(app.current_version < latest.version)
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use axoasset::reqwest::StatusCode;
+    use axoasset::serde_json::json;
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    use super::BatchUpdater;
+    use crate::{ReleaseSource, ReleaseSourceType, Version};
+
+    fn source(app_name: &str) -> ReleaseSource {
+        ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "owner".to_owned(),
+            name: "suite".to_owned(),
+            app_name: app_name.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_batch_shares_repository_fetch() {
+        let server = MockServer::start_async().await;
+        env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
+
+        let asset = |name: &str| {
+            json!({
+                "url": "u",
+                "browser_download_url": "bdu",
+                "name": name,
+            })
+        };
+        let releases_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/repos/owner/suite/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!([{
+                        "tag_name": "v1.0.0",
+                        "name": "v1.0.0",
+                        "url": "u",
+                        "assets": [asset("one-installer.sh"), asset("two-installer.sh")],
+                        "prerelease": false,
+                    }]));
+            })
+            .await;
+
+        let mut batch = BatchUpdater::new();
+        batch
+            .add_app("one", source("one"), Version::parse("0.9.0").unwrap())
+            .add_app("two", source("two"), Version::parse("1.0.0").unwrap())
+            .add_app("three", source("three"), Version::parse("1.0.0").unwrap());
+        let results = batch.check().await;
+        env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        releases_call.assert_hits_async(1).await;
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["one", "two", "three"]);
+        assert!(*results[0].1.as_ref().unwrap());
+        assert!(!*results[1].1.as_ref().unwrap());
+        // "three" has no installers in this repository
+        assert!(results[2].1.is_err());
+    }
+}
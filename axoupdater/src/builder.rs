@@ -0,0 +1,222 @@
+//! Constructing an updater from a chain of settings
+
+use std::time::Duration;
+
+use camino::Utf8PathBuf;
+
+use crate::{errors::*, AxoUpdater, ProxyConfig, ReleaseSource, UpdateRequest, Version};
+
+/// Builds an `AxoUpdater` declaratively, checking that it's fully configured
+/// before it's used. Create one with `AxoUpdater::builder`.
+///
+/// This is most useful when the release source and current version are
+/// known up front. Updaters which read them from the install receipt can
+/// be built without them and call `load_receipt` afterwards.
+#[derive(Default)]
+pub struct AxoUpdaterBuilder {
+    name: Option<String>,
+    source: Option<ReleaseSource>,
+    version_specifier: Option<UpdateRequest>,
+    current_version: Option<Version>,
+    github_token: Option<String>,
+    axo_token: Option<String>,
+    installer_path: Option<Utf8PathBuf>,
+    install_prefix: Option<Utf8PathBuf>,
+    timeout: Option<Duration>,
+    installer_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    no_proxy: bool,
+    proxy: Option<ProxyConfig>,
+}
+
+impl AxoUpdater {
+    /// Returns a builder for an `AxoUpdater`.
+    pub fn builder() -> AxoUpdaterBuilder {
+        AxoUpdaterBuilder::default()
+    }
+}
+
+impl AxoUpdaterBuilder {
+    /// Sets the name of the app to update. Required.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets where releases are fetched from; see
+    /// `AxoUpdater::set_release_source`.
+    pub fn source(mut self, source: ReleaseSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Sets which version to update to; see
+    /// `AxoUpdater::configure_version_specifier`.
+    pub fn version_specifier(mut self, version_specifier: UpdateRequest) -> Self {
+        self.version_specifier = Some(version_specifier);
+        self
+    }
+
+    /// Sets the installed version; see `AxoUpdater::set_current_version`.
+    pub fn current_version(mut self, version: Version) -> Self {
+        self.current_version = Some(version);
+        self
+    }
+
+    /// Sets the token used for GitHub requests; see
+    /// `AxoUpdater::set_github_token`.
+    pub fn github_token(mut self, token: impl Into<String>) -> Self {
+        self.github_token = Some(token.into());
+        self
+    }
+
+    /// Sets the token used for Axo Releases requests; see
+    /// `AxoUpdater::set_axo_token`.
+    pub fn axo_token(mut self, token: impl Into<String>) -> Self {
+        self.axo_token = Some(token.into());
+        self
+    }
+
+    /// Runs a local installer instead of downloading one; see
+    /// `AxoUpdater::configure_installer_path`.
+    pub fn installer_path(mut self, path: impl Into<Utf8PathBuf>) -> Self {
+        self.installer_path = Some(path.into());
+        self
+    }
+
+    /// Installs to `prefix` instead of the receipt's install prefix; see
+    /// `AxoUpdater::set_install_prefix`.
+    pub fn install_prefix(mut self, prefix: impl Into<Utf8PathBuf>) -> Self {
+        self.install_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the timeout for each network request; see
+    /// `AxoUpdater::set_request_timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits how long the installer may run; see
+    /// `AxoUpdater::set_installer_timeout`.
+    pub fn installer_timeout(mut self, timeout: Duration) -> Self {
+        self.installer_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the User-Agent sent with each network request; see
+    /// `AxoUpdater::set_user_agent`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Ignores any proxy configured in the environment; see
+    /// `AxoUpdater::set_no_proxy`.
+    pub fn no_proxy(mut self, setting: bool) -> Self {
+        self.no_proxy = setting;
+        self
+    }
+
+    /// Sets the proxies to send network requests through; see
+    /// `AxoUpdater::set_proxy`.
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Builds the updater. Fails with `AxoupdateError::NotConfigured` if
+    /// no name was set, or if any of the settings are invalid.
+    pub fn build(self) -> AxoupdateResult<AxoUpdater> {
+        let Some(name) = self.name else {
+            return Err(AxoupdateError::NotConfigured {
+                missing_field: "app_name".to_owned(),
+            });
+        };
+
+        let mut updater = AxoUpdater::new_for(&name);
+        if let Some(source) = self.source {
+            updater.set_release_source(source);
+        }
+        if let Some(version_specifier) = self.version_specifier {
+            updater.configure_version_specifier(version_specifier);
+        }
+        if let Some(version) = self.current_version {
+            updater.set_current_version(version)?;
+        }
+        if let Some(token) = &self.github_token {
+            updater.set_github_token(token);
+        }
+        if let Some(token) = &self.axo_token {
+            updater.set_axo_token(token);
+        }
+        if let Some(path) = self.installer_path {
+            updater.configure_installer_path(path);
+        }
+        if let Some(prefix) = self.install_prefix {
+            updater.set_install_prefix(prefix);
+        }
+        if let Some(timeout) = self.timeout {
+            updater.set_request_timeout(timeout);
+        }
+        if let Some(timeout) = self.installer_timeout {
+            updater.set_installer_timeout(timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            updater.set_user_agent(user_agent)?;
+        }
+        updater.set_no_proxy(self.no_proxy);
+        if let Some(proxy) = self.proxy {
+            updater.set_proxy(proxy);
+        }
+
+        Ok(updater)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AxoUpdater, AxoupdateError, ReleaseSource, ReleaseSourceType, UpdateRequest};
+
+    #[test]
+    fn test_builder() {
+        let updater = AxoUpdater::builder()
+            .name("axolotlsay")
+            .source(ReleaseSource {
+                release_type: ReleaseSourceType::GitHub,
+                owner: "axodotdev".to_owned(),
+                name: "axolotlsay".to_owned(),
+                app_name: "axolotlsay".to_owned(),
+            })
+            .version_specifier(UpdateRequest::LatestMaybePrerelease)
+            .current_version("1.0.0".parse().unwrap())
+            .github_token("secret")
+            .build()
+            .unwrap();
+
+        assert_eq!(updater.name.as_deref(), Some("axolotlsay"));
+        assert_eq!(updater.source.as_ref().unwrap().owner, "axodotdev");
+        assert_eq!(
+            updater.version_specifier,
+            UpdateRequest::LatestMaybePrerelease
+        );
+        assert_eq!(updater.current_version().unwrap().to_string(), "1.0.0");
+        assert_eq!(updater.tokens.github.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        assert!(matches!(
+            AxoUpdater::builder().build(),
+            Err(AxoupdateError::NotConfigured { .. })
+        ));
+        assert!(matches!(
+            AxoUpdater::builder()
+                .name("axolotlsay")
+                .user_agent("")
+                .build(),
+            Err(AxoupdateError::NotConfigured { .. })
+        ));
+    }
+}
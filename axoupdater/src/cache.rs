@@ -0,0 +1,410 @@
+//! Caching fetched release metadata on disk
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axoasset::{LocalAsset, SourceFile};
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::*, receipt::get_config_paths, AxoUpdater, Release, UpdateRequest};
+
+/// A release, along with what it was fetched for and when
+#[derive(Deserialize, Serialize)]
+struct CachedRelease {
+    /// Identifies the source and version specifier this release was fetched for
+    key: String,
+    /// When this release was fetched, in seconds since the UNIX epoch
+    fetched_at: u64,
+    /// The release itself
+    release: Release,
+    /// The ETag of the response the release was read from, if any; used to
+    /// ask the release source whether the release has changed since
+    #[serde(default)]
+    etag: Option<String>,
+}
+
+impl AxoUpdater {
+    /// Enables caching fetched release metadata on disk, alongside the
+    /// install receipt. Within `ttl` of a fetch, later checks for the same
+    /// source and version reuse the cached release instead of querying the
+    /// release source. This is useful for apps which check for updates every
+    /// time they're launched.
+    ///
+    /// For the latest release on GitHub, the response's ETag is cached too.
+    /// Once the cached release expires, the next check asks GitHub whether
+    /// the latest release has changed since; "not modified" responses are
+    /// cheap and don't count against the API rate limit.
+    pub fn enable_release_cache(&mut self, ttl: Duration) -> &mut AxoUpdater {
+        self.release_cache_ttl = Some(ttl);
+
+        self
+    }
+
+    /// Removes any cached release metadata, so that the next check queries
+    /// the release source.
+    pub fn invalidate_cache(&mut self) -> AxoupdateResult<&mut AxoUpdater> {
+        self.requested_release = None;
+        if let Some(path) = self.release_cache_path() {
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the cached release, if caching is enabled and the cached
+    /// release is for the current source and version specifier and hasn't
+    /// expired yet.
+    pub(crate) fn cached_release(&self) -> Option<Release> {
+        let ttl = self.release_cache_ttl?;
+        let cached = self.load_cached_release()?;
+        let age = now().checked_sub(cached.fetched_at)?;
+        if age > ttl.as_secs() {
+            return None;
+        }
+
+        Some(cached.release)
+    }
+
+    /// Returns the cached release for the current source and version
+    /// specifier, whether or not it's expired.
+    fn load_cached_release(&self) -> Option<CachedRelease> {
+        self.release_cache_ttl?;
+        let path = self.release_cache_path()?;
+        if !path.exists() {
+            return None;
+        }
+
+        let cached = SourceFile::load_local(&path)
+            .ok()?
+            .deserialize_json::<CachedRelease>()
+            .ok()?;
+        if cached.key != self.release_cache_key()? {
+            return None;
+        }
+
+        Some(cached)
+    }
+
+    /// Hands an expired release, along with the ETag of the response it was
+    /// read from, to the GitHub backend the next lookup uses. The backend
+    /// sends the ETag as `If-None-Match`, and if the latest release hasn't
+    /// changed, uses the expired release again rather than refetching it.
+    /// Either way, the result goes through the same selection as any other
+    /// release.
+    pub(crate) fn prepare_revalidation(&self) {
+        let revalidation = self
+            .load_cached_release()
+            .and_then(|cached| Some((cached.etag?, cached.release)));
+        self.latest_etag.set(revalidation);
+    }
+
+    /// Writes `release` to the cache, along with the ETag of the response
+    /// it was read from, if caching is enabled. Failing to write the cache
+    /// isn't fatal; the release will be fetched again next time.
+    pub(crate) fn store_cached_release(&self, release: &Release, etag: Option<String>) {
+        if self.release_cache_ttl.is_none() {
+            return;
+        }
+        let (Some(path), Some(key)) = (self.release_cache_path(), self.release_cache_key()) else {
+            return;
+        };
+
+        let cached = CachedRelease {
+            key,
+            fetched_at: now(),
+            release: release.clone(),
+            etag,
+        };
+        let Ok(contents) = axoasset::serde_json::to_string(&cached) else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = LocalAsset::create_dir_all(parent);
+        }
+        let _ = LocalAsset::write_new(&contents, &path);
+    }
+
+    fn release_cache_path(&self) -> Option<Utf8PathBuf> {
+        let app_name = self.name.as_ref()?;
+        let config_path = get_config_paths(app_name, self.config_subdir.as_deref())
+            .ok()?
+            .into_iter()
+            .next()?;
+
+        Some(config_path.join(format!("{app_name}-release-cache.json")))
+    }
+
+    /// Releases fetched from custom backends aren't cached, since there's
+    /// no release source to identify them by.
+    fn release_cache_key(&self) -> Option<String> {
+        if self.custom_backend.is_some() {
+            return None;
+        }
+        let source = self.source.as_ref()?;
+        let specifier = match &self.version_specifier {
+            UpdateRequest::Latest => "latest".to_owned(),
+            UpdateRequest::LatestMaybePrerelease => "latest-prerelease".to_owned(),
+            UpdateRequest::Channel(channel) => format!("channel:{channel}"),
+            UpdateRequest::LatestWithinMajor => {
+                format!("latest-major:{}", self.current_version.as_ref()?.major)
+            }
+            UpdateRequest::SpecificVersion(version) => format!("version:{version}"),
+            UpdateRequest::SpecificTag(tag) => format!("tag:{tag}"),
+        };
+        // Skipping a version that's already cached should stop it being used
+        let skipped = self
+            .skip_versions
+            .iter()
+            .map(|version| format!(":skip:{version}"))
+            .collect::<String>();
+
+        Some(format!(
+            "{}:{}/{}/{}:{specifier}{skipped}",
+            source.release_type, source.owner, source.name, source.app_name
+        ))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, time::Duration};
+
+    use axoasset::reqwest::StatusCode;
+    use axoasset::serde_json::json;
+    use axoasset::{LocalAsset, SourceFile};
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    use super::CachedRelease;
+    use crate::{AxoUpdater, ReleaseSource, ReleaseSourceType};
+
+    fn updater() -> AxoUpdater {
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater
+            .set_release_source(ReleaseSource {
+                release_type: ReleaseSourceType::GitHub,
+                owner: "owner".to_owned(),
+                name: "name".to_owned(),
+                app_name: "axolotlsay".to_owned(),
+            })
+            .enable_release_cache(Duration::from_secs(60 * 60));
+        updater
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_release_cache() {
+        let server = MockServer::start_async().await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        env::set_var("AXOLOTLSAY_INSTALLER_GHE_BASE_URL", server.base_url());
+        env::set_var("AXOUPDATER_CONFIG_PATH", tempdir.path());
+
+        let latest_release_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "tag_name": "v1.0.0",
+                        "name": "v1.0.0",
+                        "url": "u",
+                        "assets": [{
+                            "url": "u",
+                            "browser_download_url": "bdu",
+                            "name": "axolotlsay-installer.sh",
+                        }],
+                        "prerelease": false,
+                    }));
+            })
+            .await;
+
+        let first = updater().query_new_version().await.unwrap().cloned();
+        // A fresh updater should read the release back from the cache
+        let second = updater().query_new_version().await.unwrap().cloned();
+        let hits_before_invalidating = latest_release_http_call.hits_async().await;
+
+        let mut invalidated = updater();
+        invalidated.invalidate_cache().unwrap();
+        let third = invalidated.query_new_version().await.unwrap().cloned();
+
+        env::remove_var("AXOLOTLSAY_INSTALLER_GHE_BASE_URL");
+        env::remove_var("AXOUPDATER_CONFIG_PATH");
+
+        assert_eq!(hits_before_invalidating, 1);
+        latest_release_http_call.assert_hits_async(2).await;
+        assert_eq!(first, second);
+        assert_eq!(second, third);
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_release_cache_etag() {
+        let server = MockServer::start_async().await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        env::set_var("AXOLOTLSAY_INSTALLER_GHE_BASE_URL", server.base_url());
+        env::set_var("AXOUPDATER_CONFIG_PATH", tempdir.path());
+
+        let latest_release_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest")
+                    .header_missing("if-none-match");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .header("etag", "\"abc123\"")
+                    .json_body(json!({
+                        "tag_name": "v1.0.0",
+                        "name": "v1.0.0",
+                        "url": "u",
+                        "assets": [{
+                            "url": "u",
+                            "browser_download_url": "bdu",
+                            "name": "axolotlsay-installer.sh",
+                        }],
+                        "prerelease": false,
+                    }));
+            })
+            .await;
+        let not_modified_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest")
+                    .header("if-none-match", "\"abc123\"");
+                then.status(StatusCode::NOT_MODIFIED.as_u16());
+            })
+            .await;
+
+        let first = updater().query_new_version().await.unwrap().cloned();
+
+        // Expire the cached release, so the next check has to revalidate it
+        let cache_path = updater().release_cache_path().unwrap();
+        let mut cached = SourceFile::load_local(&cache_path)
+            .unwrap()
+            .deserialize_json::<CachedRelease>()
+            .unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"abc123\""));
+        cached.fetched_at = 0;
+        LocalAsset::write_new(
+            &axoasset::serde_json::to_string(&cached).unwrap(),
+            &cache_path,
+        )
+        .unwrap();
+
+        let second = updater().query_new_version().await.unwrap().cloned();
+
+        env::remove_var("AXOLOTLSAY_INSTALLER_GHE_BASE_URL");
+        env::remove_var("AXOUPDATER_CONFIG_PATH");
+
+        latest_release_http_call.assert_hits_async(1).await;
+        not_modified_http_call.assert_hits_async(1).await;
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_release_cache_etag_modified() {
+        let server = MockServer::start_async().await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        env::set_var("AXOLOTLSAY_INSTALLER_GHE_BASE_URL", server.base_url());
+        env::set_var("AXOUPDATER_CONFIG_PATH", tempdir.path());
+
+        let gh_release = |tag: &str| {
+            json!({
+                "tag_name": tag,
+                "name": tag,
+                "url": "u",
+                "assets": [{
+                    "url": "u",
+                    "browser_download_url": "bdu",
+                    "name": "axolotlsay-installer.sh",
+                }],
+                "prerelease": false,
+            })
+        };
+        let first_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest")
+                    .header_missing("if-none-match");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .header("etag", "\"abc123\"")
+                    .json_body(gh_release("v1.0.0"));
+            })
+            .await;
+        let modified_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest")
+                    .header("if-none-match", "\"abc123\"");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .header("etag", "\"def456\"")
+                    .json_body(gh_release("v2.0.0"));
+            })
+            .await;
+        let list_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/name/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!([gh_release("v2.0.0"), gh_release("v1.5.0")]));
+            })
+            .await;
+        let skipping_updater = || {
+            let mut updater = updater();
+            updater.set_skip_versions(vec!["2.0.0".parse().unwrap()]);
+            updater
+        };
+
+        let first = skipping_updater()
+            .query_new_version()
+            .await
+            .unwrap()
+            .cloned();
+
+        // Expire the cached release, so the next check has to revalidate it
+        let cache_path = skipping_updater().release_cache_path().unwrap();
+        let mut cached = SourceFile::load_local(&cache_path)
+            .unwrap()
+            .deserialize_json::<CachedRelease>()
+            .unwrap();
+        cached.fetched_at = 0;
+        LocalAsset::write_new(
+            &axoasset::serde_json::to_string(&cached).unwrap(),
+            &cache_path,
+        )
+        .unwrap();
+
+        // The new latest release is skipped like any other
+        let second = skipping_updater()
+            .query_new_version()
+            .await
+            .unwrap()
+            .cloned();
+        let cached = SourceFile::load_local(&cache_path)
+            .unwrap()
+            .deserialize_json::<CachedRelease>()
+            .unwrap();
+
+        env::remove_var("AXOLOTLSAY_INSTALLER_GHE_BASE_URL");
+        env::remove_var("AXOUPDATER_CONFIG_PATH");
+
+        first_http_call.assert_hits_async(1).await;
+        modified_http_call.assert_hits_async(1).await;
+        list_http_call.assert_hits_async(1).await;
+        assert_eq!(first.unwrap().to_string(), "1.0.0");
+        assert_eq!(second.unwrap().to_string(), "1.5.0");
+        assert_eq!(cached.etag.as_deref(), Some("\"def456\""));
+    }
+}
@@ -0,0 +1,124 @@
+//! Cancelling long-running operations
+
+use std::future::Future;
+
+use futures_util::future::{self, BoxFuture, Either, FutureExt, Shared};
+
+use crate::{errors::*, AxoUpdater};
+
+/// A future which completes once the caller has asked for the current
+/// operation to be cancelled. It's shared so that it can be checked any
+/// number of times, from any number of places.
+#[derive(Clone)]
+pub(crate) struct Cancellation(Shared<BoxFuture<'static, ()>>);
+
+impl Cancellation {
+    /// Returns whether cancellation has been requested yet.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.clone().now_or_never().is_some()
+    }
+}
+
+impl AxoUpdater {
+    /// Configures a future which cancels long-running operations once it
+    /// completes, such as fetching paginated release lists and downloading
+    /// the installer. Cancelled operations stop at the next opportunity and
+    /// return `AxoupdateError::Cancelled`. This is useful for GUI apps with
+    /// a "Cancel" button.
+    ///
+    /// With tokio-util, pass `token.cancelled_owned()` for a
+    /// `CancellationToken`.
+    ///
+    /// Cancellation is last checked just before the installer is run. Once
+    /// it's started, it's always allowed to finish, since stopping it
+    /// partway could leave a half-replaced installation behind.
+    pub fn set_cancellation_token(
+        &mut self,
+        cancelled: impl Future<Output = ()> + Send + 'static,
+    ) -> &mut AxoUpdater {
+        self.cancellation = Some(Cancellation(cancelled.boxed().shared()));
+
+        self
+    }
+
+    /// Returns `AxoupdateError::Cancelled` if cancellation has been requested.
+    pub(crate) fn check_cancelled(&self) -> AxoupdateResult<()> {
+        match &self.cancellation {
+            Some(cancellation) if cancellation.is_cancelled() => Err(AxoupdateError::Cancelled {}),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs `operation` until it finishes or cancellation is requested,
+    /// whichever comes first. A cancelled operation is dropped without
+    /// being polled again.
+    pub(crate) async fn cancellable<T>(
+        &self,
+        operation: impl Future<Output = AxoupdateResult<T>>,
+    ) -> AxoupdateResult<T> {
+        let Some(cancellation) = &self.cancellation else {
+            return operation.await;
+        };
+
+        futures_util::pin_mut!(operation);
+        match future::select(operation, cancellation.0.clone()).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::info!("operation cancelled");
+                Err(AxoupdateError::Cancelled {})
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use futures_util::future;
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    use crate::{AxoUpdater, AxoupdateError, ReleaseSource, ReleaseSourceType};
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_cancellation() {
+        let server = MockServer::start_async().await;
+        std::env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path_contains("/repos/owner/name/releases");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .delay(Duration::from_secs(30))
+                    .body("[]");
+            })
+            .await;
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater.set_release_source(ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "owner".to_owned(),
+            name: "name".to_owned(),
+            app_name: "app".to_owned(),
+        });
+        assert!(updater.check_cancelled().is_ok());
+
+        // A slow request is abandoned as soon as cancellation is requested
+        updater.set_cancellation_token(tokio::time::sleep(Duration::from_millis(200)));
+        let started = Instant::now();
+        let result = updater.peek_latest_release().await;
+        std::env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        assert!(matches!(result, Err(AxoupdateError::Cancelled {})));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        updater.set_cancellation_token(future::ready(()));
+        assert!(matches!(
+            updater.check_cancelled(),
+            Err(AxoupdateError::Cancelled {})
+        ));
+    }
+}
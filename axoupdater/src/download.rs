@@ -0,0 +1,611 @@
+//! Downloading release assets other than the installer
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use axoasset::reqwest::{
+    self,
+    header::{ACCEPT_RANGES, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE},
+    StatusCode,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::{errors::*, verify::sha256_hex, Asset, AxoUpdater};
+
+impl AxoUpdater {
+    /// Downloads the first asset of the release this updater would install
+    /// which matches `predicate` into the directory `dest`, returning the
+    /// path it was written to. This is useful for fetching companion assets,
+    /// like shell completions or man pages, alongside an update.
+    ///
+    /// The release is fetched first if it hasn't been already. The download
+    /// uses the same tokens and HTTP client configuration as the installer.
+    pub async fn download_asset(
+        &mut self,
+        predicate: impl Fn(&Asset) -> bool,
+        dest: &Utf8Path,
+    ) -> AxoupdateResult<Utf8PathBuf> {
+        if self.requested_release.is_none() {
+            self.fetch_release().await?;
+        }
+        let release = self.requested_release.as_ref().unwrap();
+
+        let Some(asset) = release.assets.iter().find(|asset| predicate(asset)) else {
+            return Err(AxoupdateError::NoMatchingAsset {
+                tag: release.tag_name.to_owned(),
+            });
+        };
+
+        std::fs::create_dir_all(dest)?;
+        let path = dest.join(&asset.name);
+
+        let client = self.http_client()?;
+        self.stream_asset_to_file(&client, asset, path.as_std_path())
+            .await?;
+
+        Ok(path)
+    }
+
+    #[cfg(feature = "blocking")]
+    /// Identical to Axoupdater::download_asset(), but performed synchronously.
+    pub fn download_asset_sync(
+        &mut self,
+        predicate: impl Fn(&Asset) -> bool,
+        dest: &Utf8Path,
+    ) -> AxoupdateResult<Utf8PathBuf> {
+        crate::block_on(self.download_asset(predicate, dest))?
+    }
+
+    /// Configures whether interrupted installer and archive downloads should
+    /// be resumed rather than restarted. When enabled, downloads are written
+    /// to a partial file as they arrive; if a download fails and the server
+    /// advertised `Accept-Ranges: bytes` along with an `ETag` or
+    /// `Last-Modified` header, the partial file is kept, and the next attempt
+    /// asks for just the rest of it. That request is conditional on the asset
+    /// being unchanged, so a partial file is never completed with the tail
+    /// of a different asset. This helps with large archives on flaky or
+    /// metered connections. The completed download is checked against the
+    /// size the server reported, as well as the configured checksum and
+    /// signature checks. Defaults to false.
+    ///
+    /// Partial files are kept in a directory only the current user can
+    /// access: `axoupdater-downloads` in the staging directory override, if
+    /// one is set, or in the user's cache directory otherwise. If neither is
+    /// usable, downloads aren't resumable.
+    pub fn set_resume_downloads(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.resume_downloads = setting;
+
+        self
+    }
+
+    /// Downloads `asset` to a new file at `dest` without holding it all in
+    /// memory, resuming an earlier, interrupted download of it if resuming
+    /// downloads is enabled. Returns the size of the download.
+    pub(crate) async fn download_asset_to(
+        &self,
+        client: &reqwest::Client,
+        asset: &Asset,
+        dest: &Path,
+    ) -> AxoupdateResult<u64> {
+        if !self.resume_downloads {
+            return self.stream_asset_to_file(client, asset, dest).await;
+        }
+
+        let path = match self.partial_download_path(asset) {
+            Ok(path) => path,
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(error = %_e, "no private directory for partial downloads; not resuming");
+                return self.stream_asset_to_file(client, asset, dest).await;
+            }
+        };
+        let validator_path = path.with_extension("validator");
+        // Without the validator, we can't be sure the rest of the asset
+        // we'd be sent is the rest of the same asset
+        let validator = std::fs::read_to_string(&validator_path).ok();
+        let mut file = open_partial_file(&path, validator.is_some())?;
+        let mut offset = file.metadata()?.len();
+
+        let mut response = loop {
+            let mut request = self.asset_request(client, asset);
+            if let Some(validator) = validator.as_ref().filter(|_| offset > 0) {
+                request = request
+                    .header(RANGE, format!("bytes={offset}-"))
+                    .header(IF_RANGE, validator);
+            }
+            let response = self
+                .cancellable(async { AxoupdateResult::Ok(request.send().await?) })
+                .await?;
+            // The partial file was already complete, or is from an asset
+            // which has since changed; start again from scratch
+            if offset > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                file.set_len(0)?;
+                offset = 0;
+                continue;
+            }
+            break response.error_for_status()?;
+        };
+        let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+        if offset > 0 && !resumed {
+            // The server ignored the range and sent the whole asset
+            #[cfg(feature = "tracing")]
+            tracing::debug!(url = %asset.browser_download_url, "server can't resume the download; restarting it");
+            file.set_len(0)?;
+            offset = 0;
+        }
+        if !resumed {
+            // Remember what this download is of, so that it's only ever
+            // resumed with more of the same
+            let _ = std::fs::remove_file(&validator_path);
+            if let Some(validator) = resume_validator(&response) {
+                create_private_file(&validator_path)?.write_all(validator.as_bytes())?;
+            }
+        }
+        let resumable = validator_path.is_file()
+            && (resumed
+                || response
+                    .headers()
+                    .get(ACCEPT_RANGES)
+                    .is_some_and(|value| value == "bytes"));
+        let expected = if resumed {
+            response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(content_range_total)
+                .or_else(|| response.content_length().map(|length| offset + length))
+        } else {
+            response.content_length()
+        };
+
+        #[cfg(feature = "tracing")]
+        if resumed {
+            tracing::info!(url = %asset.browser_download_url, offset, "resuming download");
+        }
+
+        let mut written = offset;
+        let streamed: AxoupdateResult<u64> = async {
+            while let Some(chunk) = self
+                .cancellable(async { AxoupdateResult::Ok(response.chunk().await?) })
+                .await?
+            {
+                file.write_all(&chunk)?;
+                written += chunk.len() as u64;
+            }
+            file.flush()?;
+            check_download_length(expected, written)
+        }
+        .await;
+        drop(file);
+
+        match streamed {
+            Ok(written) => {
+                move_file(&path, dest)?;
+                let _ = std::fs::remove_file(&validator_path);
+                Ok(written)
+            }
+            Err(e) => {
+                // Keep what we've got for next time, if the server will let
+                // us pick up where we left off
+                let keep = resumable
+                    && match &e {
+                        AxoupdateError::Reqwest(_) | AxoupdateError::Cancelled {} => true,
+                        AxoupdateError::IncompleteDownload { expected, got } => got < expected,
+                        _ => false,
+                    };
+                if !keep {
+                    let _ = std::fs::remove_file(&path);
+                    let _ = std::fs::remove_file(&validator_path);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Where a resumable download of `asset` is written while it's in
+    /// progress, creating its directory if needed. This is named after the
+    /// asset's URL, so that a partial download is only ever resumed from the
+    /// same place.
+    fn partial_download_path(&self, asset: &Asset) -> AxoupdateResult<PathBuf> {
+        let dir = match &self.staging_dir_override {
+            Some(dir) => dir.join("axoupdater-downloads"),
+            None => user_cache_dir()
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?
+                .join("axoupdater")
+                .join("downloads"),
+        };
+        create_private_dir(&dir)?;
+        let key = sha256_hex(asset.browser_download_url.as_bytes());
+
+        Ok(dir.join(format!("{}-{}.part", &key[..16], asset.name)))
+    }
+
+    /// Downloads `asset` to a new file at `path`, which is removed again if
+    /// the download fails. Returns the size of the download.
+    async fn stream_asset_to_file(
+        &self,
+        client: &reqwest::Client,
+        asset: &Asset,
+        path: &Path,
+    ) -> AxoupdateResult<u64> {
+        let mut file = File::create(path)?;
+        let streamed = self.stream_asset(client, asset, &mut file).await;
+        if streamed.is_err() {
+            // Don't leave a partial download behind
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+
+        streamed
+    }
+
+    /// Downloads `asset`, writing it to `writer` as it arrives rather than
+    /// holding it all in memory. Returns the number of bytes written.
+    ///
+    /// If the server advertised a `Content-Length`, receiving any other
+    /// number of bytes is an error, since a truncated installer may still
+    /// be runnable.
+    pub(crate) async fn stream_asset(
+        &self,
+        client: &reqwest::Client,
+        asset: &Asset,
+        writer: &mut impl Write,
+    ) -> AxoupdateResult<u64> {
+        let request = self.asset_request(client, asset);
+        let mut response = self
+            .cancellable(async { AxoupdateResult::Ok(request.send().await?) })
+            .await?
+            .error_for_status()?;
+        let expected = response.content_length();
+
+        let mut written = 0;
+        while let Some(chunk) = self
+            .cancellable(async { AxoupdateResult::Ok(response.chunk().await?) })
+            .await?
+        {
+            writer.write_all(&chunk)?;
+            written += chunk.len() as u64;
+        }
+        writer.flush()?;
+
+        check_download_length(expected, written)
+    }
+}
+
+/// The current user's cache directory.
+fn user_cache_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return std::env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    }
+    let home = homedir::my_home().ok().flatten();
+    if cfg!(target_os = "macos") {
+        return home.map(|home| home.join("Library").join("Caches"));
+    }
+
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| home.join(".cache")))
+}
+
+/// Creates `dir` if needed, making sure that only the current user can
+/// access it. This fails if it's a symlink, or belongs to somebody else.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if !std::fs::symlink_metadata(dir)?.is_dir() {
+            return Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists));
+        }
+        // It may have already existed with looser permissions; only its
+        // owner can change them
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    Ok(())
+}
+
+/// Creates a new file at `path` for appending to, which only the current
+/// user can read. This never follows a symlink, or reuses an existing file.
+fn create_private_file(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.append(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)
+}
+
+/// Opens the partial download at `path` for appending to. An existing
+/// partial file is only reused if `resume` is set and it's a regular file;
+/// anything else there is replaced with a new, empty file.
+fn open_partial_file(path: &Path, resume: bool) -> std::io::Result<File> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if resume && metadata.is_file() => OpenOptions::new().append(true).open(path),
+        Ok(_) => {
+            std::fs::remove_file(path)?;
+            create_private_file(path)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => create_private_file(path),
+        Err(e) => Err(e),
+    }
+}
+
+/// Moves the file at `from` to `to`, copying it if they're on different
+/// volumes.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)?;
+    }
+
+    Ok(())
+}
+
+/// Picks the value to send as `If-Range` when resuming the download
+/// `response` started: its `ETag`, if it's a strong one, or its
+/// `Last-Modified` date otherwise.
+fn resume_validator(response: &reqwest::Response) -> Option<String> {
+    let header = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+
+    header(ETAG)
+        // Weak ETags can't be used with If-Range
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED))
+        .map(|value| value.to_owned())
+}
+
+/// Reads the full size of the asset from a `Content-Range` header, such as
+/// `bytes 100-999/1000`.
+fn content_range_total(content_range: &str) -> Option<u64> {
+    content_range.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// Checks that the number of bytes downloaded matches the length the server
+/// advertised, if it advertised one.
+fn check_download_length(expected: Option<u64>, got: u64) -> AxoupdateResult<u64> {
+    match expected {
+        Some(expected) if expected != got => {
+            Err(AxoupdateError::IncompleteDownload { expected, got })
+        }
+        _ => Ok(got),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_download_length, content_range_total};
+    use crate::test::fixtures::{release_with_assets, served_asset};
+    use crate::{AxoUpdater, AxoupdateError, Release};
+    use camino::Utf8Path;
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    fn release(server: &MockServer) -> Release {
+        release_with_assets(
+            "1.0.0",
+            vec![
+                served_asset(server, "app-installer.sh"),
+                served_asset(server, "app-completions.tar.gz"),
+            ],
+        )
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_download_asset() {
+        let server = MockServer::start_async().await;
+        let completions = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/app-completions.tar.gz");
+                then.status(200).body("completions");
+            })
+            .await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let dest = Utf8Path::from_path(tempdir.path()).unwrap().join("assets");
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater.requested_release = Some(release(&server));
+
+        let path = updater
+            .download_asset(|asset| asset.name.contains("completions"), &dest)
+            .await
+            .unwrap();
+        completions.assert_async().await;
+        assert_eq!(path, dest.join("app-completions.tar.gz"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "completions");
+
+        assert!(matches!(
+            updater
+                .download_asset(|asset| asset.name.ends_with(".deb"), &dest)
+                .await,
+            Err(AxoupdateError::NoMatchingAsset { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_resume_download() {
+        let server = MockServer::start_async().await;
+        let resumed = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/app-installer.sh")
+                    .header("range", "bytes=5-")
+                    .header("if-range", "\"v1\"");
+                then.status(206)
+                    .header("accept-ranges", "bytes")
+                    .header("content-range", "bytes 5-10/11")
+                    .body(" world");
+            })
+            .await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater
+            .set_resume_downloads(true)
+            .set_staging_dir_override(tempdir.path().to_owned());
+        let release = release(&server);
+        let installer = &release.assets[0];
+        // Left behind by an earlier attempt
+        let partial = updater.partial_download_path(installer).unwrap();
+        std::fs::write(&partial, "hello").unwrap();
+        std::fs::write(partial.with_extension("validator"), "\"v1\"").unwrap();
+
+        let client = updater.http_client().unwrap();
+        let dest = tempdir.path().join("installer.sh");
+        updater
+            .download_asset_to(&client, installer, &dest)
+            .await
+            .unwrap();
+        let download = std::fs::read(&dest).unwrap();
+
+        resumed.assert_async().await;
+        assert_eq!(download, b"hello world");
+        assert!(!partial.exists());
+        assert!(!partial.with_extension("validator").exists());
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_resume_download_unsupported() {
+        let server = MockServer::start_async().await;
+        // This server always sends the whole asset
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/app-installer.sh");
+                then.status(200).body("hello world");
+            })
+            .await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater
+            .set_resume_downloads(true)
+            .set_staging_dir_override(tempdir.path().to_owned());
+        let release = release(&server);
+        let installer = &release.assets[0];
+        let partial = updater.partial_download_path(installer).unwrap();
+        std::fs::write(&partial, "stale").unwrap();
+        std::fs::write(partial.with_extension("validator"), "\"v1\"").unwrap();
+
+        let client = updater.http_client().unwrap();
+        let dest = tempdir.path().join("installer.sh");
+        updater
+            .download_asset_to(&client, installer, &dest)
+            .await
+            .unwrap();
+        let download = std::fs::read(&dest).unwrap();
+
+        assert_eq!(download, b"hello world");
+        assert!(!partial.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_resume_download_ignores_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/app-installer.sh");
+                then.status(200).body("hello world");
+            })
+            .await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let victim = tempdir.path().join("victim");
+        std::fs::write(&victim, "precious").unwrap();
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater
+            .set_resume_downloads(true)
+            .set_staging_dir_override(tempdir.path().to_owned());
+        let release = release(&server);
+        let installer = &release.assets[0];
+        let partial = updater.partial_download_path(installer).unwrap();
+        let mode = std::fs::metadata(partial.parent().unwrap())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o700);
+        std::os::unix::fs::symlink(&victim, &partial).unwrap();
+        std::fs::write(partial.with_extension("validator"), "\"v1\"").unwrap();
+
+        let client = updater.http_client().unwrap();
+        let dest = tempdir.path().join("installer.sh");
+        updater
+            .download_asset_to(&client, installer, &dest)
+            .await
+            .unwrap();
+        let download = std::fs::read(&dest).unwrap();
+
+        assert_eq!(download, b"hello world");
+        assert_eq!(std::fs::read_to_string(&victim).unwrap(), "precious");
+    }
+
+    #[test]
+    fn test_content_range_total() {
+        assert_eq!(content_range_total("bytes 5-10/11"), Some(11));
+        assert_eq!(content_range_total("bytes 5-10/*"), None);
+        assert_eq!(content_range_total("nonsense"), None);
+    }
+
+    #[test]
+    fn test_check_download_length() {
+        assert_eq!(check_download_length(Some(10), 10).unwrap(), 10);
+        assert_eq!(check_download_length(None, 10).unwrap(), 10);
+        assert!(matches!(
+            check_download_length(Some(100), 10),
+            Err(AxoupdateError::IncompleteDownload {
+                expected: 100,
+                got: 10
+            })
+        ));
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_download_asset_failure() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/app-completions.tar.gz");
+                then.status(404);
+            })
+            .await;
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let dest = Utf8Path::from_path(tempdir.path()).unwrap();
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater.requested_release = Some(release(&server));
+
+        assert!(updater
+            .download_asset(|asset| asset.name.contains("completions"), dest)
+            .await
+            .is_err());
+        assert!(!dest.join("app-completions.tar.gz").exists());
+    }
+}
@@ -85,6 +85,30 @@ pub enum AxoupdateError {
         app_name: String,
     },
 
+    /// Indicates that the install receipt exists, but isn't valid JSON; for
+    /// example, because it was only partially written.
+    #[error("The install receipt for {app_name} at {path} is corrupt: {detail}")]
+    #[diagnostic(help(
+        "Reinstall {app_name} with its installer to write a new receipt, then try updating again."
+    ))]
+    ReceiptCorrupt {
+        /// This app's name
+        app_name: String,
+        /// The path to the receipt
+        path: camino::Utf8PathBuf,
+        /// The parse error, including where in the receipt it occurred
+        detail: String,
+    },
+
+    /// Indicates that the install receipt for this app couldn't be written.
+    #[error("Unable to save installation information for app {app_name}: {message}")]
+    ReceiptSaveFailed {
+        /// This app's name
+        app_name: String,
+        /// Why the receipt couldn't be written
+        message: String,
+    },
+
     /// Not a generic receipt load failure, but the receipt itself doesn't exist.
     #[error("Unable to load receipt for app {app_name}")]
     #[diagnostic(help(
@@ -117,10 +141,50 @@ pub enum AxoupdateError {
     NoInstallerForPackage {},
 
     /// Indicates that no stable releases exist for the app being updated.
-    #[error("There are no stable releases available for {app_name}")]
+    #[error(
+        "There are no stable releases available for {app_name}{}",
+        describe_status(status, message)
+    )]
     NoStableReleases {
         /// This app's name
         app_name: String,
+        /// The HTTP status returned by the release host, if any
+        status: Option<u16>,
+        /// The error message returned by the release host, if any
+        message: Option<String>,
+    },
+
+    /// Indicates that the repository named by the release source doesn't
+    /// exist.
+    #[error(
+        "The repository {owner}/{name} wasn't found{}",
+        describe_status(status, message)
+    )]
+    #[diagnostic(help("Check the owner and name of the release source."))]
+    ReleaseSourceNotFound {
+        /// The repository's owner
+        owner: String,
+        /// The repository's name
+        name: String,
+        /// The HTTP status returned by the release host, if any
+        status: Option<u16>,
+        /// The error message returned by the release host, if any
+        message: Option<String>,
+    },
+
+    /// Indicates that the release source's repository exists, but none of
+    /// its recent releases contain installers for the app.
+    #[error("None of the recent releases of {owner}/{name} have installers for {app_name}")]
+    #[diagnostic(help(
+        "Check the release source's app name; if this app's installers are named differently, configure them with set_installer_asset_pattern."
+    ))]
+    NoInstallersFound {
+        /// This app's name
+        app_name: String,
+        /// The repository's owner
+        owner: String,
+        /// The repository's name
+        name: String,
     },
 
     /// Indicates that no releases exist for this app at all.
@@ -133,7 +197,10 @@ pub enum AxoupdateError {
     },
 
     /// Indicates that no releases exist for this app at all.
-    #[error("The version {version} was not found for the app {app_name} in workspace {name}")]
+    #[error(
+        "The version {version} was not found for the app {app_name} in workspace {name}{}",
+        describe_status(status, message)
+    )]
     VersionNotFound {
         /// The workspace's name
         name: String,
@@ -141,6 +208,192 @@ pub enum AxoupdateError {
         app_name: String,
         /// The version we failed to find
         version: String,
+        /// The HTTP status returned by the release host, if any
+        status: Option<u16>,
+        /// The error message returned by the release host, if any
+        message: Option<String>,
+    },
+
+    /// Indicates that the release host rejected our credentials, or that
+    /// they don't grant access to the requested repository.
+    #[error("The release host rejected the request with status {status}{}", describe_status(&None, message))]
+    #[diagnostic(help("Check that your token is valid and has access to this repository."))]
+    Unauthorized {
+        /// The HTTP status returned by the release host
+        status: u16,
+        /// The error message returned by the release host, if any
+        message: Option<String>,
+    },
+
+    /// Indicates that GitHub couldn't find the repository even though a
+    /// token was supplied. Fine-grained tokens get a 404 rather than a 403
+    /// for repositories they don't grant access to, so this usually means
+    /// the token can't see the repository rather than that it's missing.
+    #[error("{url} wasn't found{}; the GitHub token may not have access to this repository", describe_status(&None, message))]
+    #[diagnostic(help(
+        "Check that your token grants access to this repository; fine-grained tokens only work for the repositories they were created for."
+    ))]
+    PossiblyUnauthorized {
+        /// The URL that wasn't found
+        url: String,
+        /// The error message returned by GitHub, if any
+        message: Option<String>,
+    },
+
+    /// Indicates that the release host couldn't be reached, for example
+    /// because the connection failed or timed out. Unlike other errors from
+    /// the release host, trying again later may succeed.
+    #[error("Unable to connect to {host}")]
+    #[diagnostic(help("Check your network connection, or try again later."))]
+    NetworkError {
+        /// The release host that couldn't be reached
+        host: String,
+        /// The underlying error
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// Indicates that the release marked as latest is older than the version
+    /// that's already installed
+    #[error("The latest release, {latest}, is older than the installed version, {installed}")]
+    #[diagnostic(help("The wrong release may have been marked as latest."))]
+    LatestIsOlder {
+        /// The installed version
+        installed: String,
+        /// The version of the release marked as latest
+        latest: String,
+    },
+
+    /// Indicates that a blocking method was called from within an async
+    /// runtime, where it would otherwise panic
+    #[error("A blocking (_sync) method was called from within an async runtime")]
+    #[diagnostic(help(
+        "Use the async version of this method instead, for example run() instead of run_sync()."
+    ))]
+    BlockingInAsyncContext {},
+
+    /// Indicates that the blocking runtime was configured after it had
+    /// already been created
+    #[error("The blocking runtime has already been created")]
+    #[diagnostic(help(
+        "Call set_max_blocking_threads before calling any of the blocking (_sync) methods."
+    ))]
+    BlockingRuntimeInitialized {},
+
+    /// Indicates that none of the candidate staging directories can have
+    /// an installer run from them, for example because they're all mounted
+    /// `noexec`
+    #[error("Unable to find a directory the installer can be run from; tried:\n{tried}")]
+    #[diagnostic(help(
+        "Use set_staging_dir_override to point at a directory which allows executing files."
+    ))]
+    NoExecutableStagingDir {
+        /// Each directory that was tried and why it couldn't be used, one per line
+        tried: String,
+    },
+
+    /// Indicates that the primary release source and every fallback source
+    /// failed
+    #[error("Unable to fetch releases from any of the configured release sources")]
+    #[diagnostic(help(
+        "The release hosts may be unavailable or rate limiting requests; try again later."
+    ))]
+    AllSourcesFailed {
+        /// The error from each source, in the order they were tried
+        #[related]
+        errors: Vec<AxoupdateError>,
+    },
+
+    /// Indicates that a `BatchUpdater` couldn't fetch the releases of a
+    /// repository shared by one or more of its apps
+    #[error("Unable to fetch releases from {owner}/{name}: {message}")]
+    BatchFetchFailed {
+        /// The repository's owner
+        owner: String,
+        /// The repository's name
+        name: String,
+        /// A description of the failure
+        message: String,
+    },
+
+    /// Indicates that a download ended before all of the bytes the server
+    /// advertised were received
+    #[error("Download was incomplete; expected {expected} bytes, but got {got}")]
+    #[diagnostic(help(
+        "The connection may have been interrupted, or a proxy may have truncated the response. Try again."
+    ))]
+    IncompleteDownload {
+        /// The length the server advertised
+        expected: u64,
+        /// The number of bytes actually received
+        got: u64,
+    },
+
+    /// Indicates that none of a release's assets matched the ones requested
+    #[error("None of the assets in release {tag} matched")]
+    NoMatchingAsset {
+        /// The release's tag
+        tag: String,
+    },
+
+    /// Indicates that a binary listed in the install receipt wasn't found
+    /// in the release's archive
+    #[error("The release's archive doesn't contain {binary}")]
+    BinaryNotInArchive {
+        /// The binary's name
+        binary: String,
+    },
+
+    /// Indicates that the running executable isn't managed by the install
+    /// receipt, so uninstalling it could remove another installation
+    #[error("Refusing to uninstall {app_name}; this copy wasn't installed by its installer")]
+    #[diagnostic(help(
+        "If you installed {app_name} with a package manager, use it to uninstall instead."
+    ))]
+    UninstallNotEligible {
+        /// The app's name
+        app_name: String,
+    },
+
+    /// Indicates that the app's bundled uninstaller failed
+    #[error("The uninstaller failed: {message}")]
+    UninstallFailed {
+        /// The exit status of the uninstaller, if it exited
+        status: Option<i32>,
+        /// A description of the failure
+        message: String,
+    },
+
+    /// Indicates that an installer was selected for a different platform
+    /// than the one we're running on
+    #[error("Can't run an installer for {os} {arch} on this platform")]
+    #[diagnostic(help(
+        "A target platform was set for installer selection; remove it to update this machine."
+    ))]
+    TargetPlatformMismatch {
+        /// The target OS
+        os: String,
+        /// The target architecture
+        arch: String,
+    },
+
+    /// Indicates that the requested release is older than the configured
+    /// minimum version
+    #[error("Refusing to install {requested}, which is older than the minimum version {minimum}")]
+    VersionBelowMinimum {
+        /// The version of the resolved release
+        requested: String,
+        /// The configured minimum version
+        minimum: String,
+    },
+
+    /// Indicates that the requested version was excluded from updates with
+    /// `AxoUpdater::set_skip_versions`
+    #[error("Refusing to install {version}, which has been marked to be skipped")]
+    #[diagnostic(help("Request a different version, or remove {version} from the skip list."))]
+    VersionSkipped {
+        /// The skipped version
+        version: String,
     },
 
     /// This error catches an edge case where the axoupdater executable was run
@@ -174,6 +427,123 @@ pub enum AxoupdateError {
         stderr: Option<String>,
     },
 
+    /// Indicates that the installer didn't finish within the configured
+    /// timeout and was killed
+    #[error("The installer didn't finish within {} seconds and was stopped", timeout.as_secs_f64())]
+    #[diagnostic(help(
+        "The installer may be waiting for input or stuck on a slow network; try running the update again."
+    ))]
+    InstallerTimedOut {
+        /// The timeout that was exceeded
+        timeout: std::time::Duration,
+    },
+
+    /// Indicates that the install prefix can't be written to
+    #[error("The install prefix {path} isn't writable")]
+    #[diagnostic(help(
+        "Check the permissions on this directory. If it's a system-wide install, for example under /usr/local, try updating with elevated privileges, such as with sudo."
+    ))]
+    InstallPrefixNotWritable {
+        /// The install prefix
+        path: camino::Utf8PathBuf,
+    },
+
+    /// Indicates that the pre-update hook declined to allow the update
+    #[error("The update was aborted by the pre-update hook: {message}")]
+    HookAborted {
+        /// The message returned by the hook
+        message: String,
+    },
+
+    /// Indicates that the post-update hook failed. The new version has
+    /// already been installed when this is returned.
+    #[error("The update was installed, but the post-update hook failed: {message}")]
+    PostUpdateHookFailed {
+        /// The message returned by the hook
+        message: String,
+    },
+
+    /// Indicates that the operation was cancelled with the future passed to
+    /// `AxoUpdater::set_cancellation_token`
+    #[error("The operation was cancelled")]
+    Cancelled {},
+
+    /// Indicates that the newly installed version didn't pass post-update
+    /// verification. The new version has already been installed when this
+    /// is returned.
+    #[error("The update was installed, but the new version failed verification: {message}")]
+    PostUpdateVerificationFailed {
+        /// The exit status of the verification command, if it ran
+        status: Option<i32>,
+        /// Why verification failed
+        message: String,
+    },
+
+    /// Indicates that the installer's signature couldn't be verified
+    #[error("Unable to verify the installer's signature: {message}")]
+    #[diagnostic(help("The installer may have been tampered with; it has not been run."))]
+    SignatureVerificationFailed {
+        /// The reason verification failed
+        message: String,
+    },
+
+    /// Indicates that the installer's checksum didn't match the one recorded
+    /// in the release's dist manifest
+    #[error("Unable to verify the installer's checksum: {message}")]
+    #[diagnostic(help("The installer may have been tampered with; it has not been run."))]
+    ChecksumVerificationFailed {
+        /// The reason verification failed
+        message: String,
+    },
+
+    /// Indicates that the downloaded installer doesn't look like an
+    /// installer script, for example because an HTML error page was served
+    /// in its place
+    #[error("The downloaded installer is invalid: {reason}")]
+    #[diagnostic(help(
+        "The release host may have served an error page; try again later. The installer has not been run."
+    ))]
+    InvalidInstaller {
+        /// Why the installer was rejected
+        reason: String,
+    },
+
+    /// Indicates that an archive's format couldn't be determined from its name
+    #[error("Unable to determine the archive format of {path}")]
+    #[diagnostic(help("Supported formats are .tar.gz, .tar.xz, .tar.zst and .zip."))]
+    UnknownArchiveFormat {
+        /// The path to the archive
+        path: camino::Utf8PathBuf,
+    },
+
+    /// Indicates that the running executable couldn't be moved aside before
+    /// running the installer
+    #[error("Unable to move the running executable from {} to {}", from.display(), to.display())]
+    #[diagnostic(help(
+        "Check that you have permission to modify the directory the executable is installed in."
+    ))]
+    SelfMoveFailed {
+        /// The executable's path
+        from: std::path::PathBuf,
+        /// Where it was being moved to
+        to: std::path::PathBuf,
+        /// The underlying error
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Indicates that, after a failed update, the previous version couldn't be
+    /// moved back into place
+    #[error(
+        "The update failed, and the previous version couldn't be restored. It was left at {}",
+        path.display()
+    )]
+    #[diagnostic(help("Move it back to its original location to restore it."))]
+    SelfRestoreFailed {
+        /// Where the previous version was left
+        path: std::path::PathBuf,
+    },
+
     /// self_replace/self_delete failed
     #[error(
         "Cleaning up the previous version failed; a copy of the old version has been left behind."
@@ -203,3 +573,13 @@ pub enum AxoupdateError {
         url: String,
     },
 }
+
+/// Formats the HTTP status and message attached to an error, if any.
+fn describe_status(status: &Option<u16>, message: &Option<String>) -> String {
+    match (status, message) {
+        (Some(status), Some(message)) => format!(" (HTTP {status}: {message})"),
+        (Some(status), None) => format!(" (HTTP {status})"),
+        (None, Some(message)) => format!(": {message}"),
+        (None, None) => String::new(),
+    }
+}
@@ -3,37 +3,66 @@
 
 //! axoupdater crate
 
+#[cfg(feature = "archive_install")]
+mod archive;
+mod batch;
+mod builder;
+mod cache;
+mod cancel;
+mod download;
 pub mod errors;
+mod platform;
+mod proxy;
 mod receipt;
 mod release;
+#[cfg(feature = "archive_install")]
+mod self_updater;
+mod staging;
+mod summary;
 pub mod test;
+mod uninstall;
+mod verify;
 
+pub use batch::BatchUpdater;
+pub use builder::AxoUpdaterBuilder;
 pub use errors::*;
+pub use platform::*;
+pub use proxy::{ProxyConfig, ProxyCredentials};
+pub use receipt::{
+    read_receipt, FilesystemReceiptStore, InstallReceipt, ReceiptProvider, ReceiptStore,
+};
 pub use release::*;
+pub use staging::{StagingChoice, StagingReason};
+pub use summary::{UpdateDirection, UpdateSummary};
+pub use verify::*;
 
 use std::{
+    collections::HashMap,
     env::{self, args},
     ffi::OsStr,
+    path::PathBuf,
     process::Stdio,
+    sync::Mutex,
+    time::Duration,
 };
 
 #[cfg(unix)]
-use std::{fs::File, os::unix::fs::PermissionsExt};
+use std::os::unix::fs::PermissionsExt;
 
 #[cfg(windows)]
 use self_replace;
 
-use axoasset::LocalAsset;
+use axoasset::reqwest;
 use axoprocess::Cmd;
 pub use axotag::Version;
-use camino::Utf8PathBuf;
-
-use tempfile::TempDir;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
 
 /// Version number for this release of axoupdater.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Provides information about the result of the upgrade operation
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct UpdateResult {
     /// The old version (pre-upgrade)
     pub old_version: Option<Version>,
@@ -46,10 +75,120 @@ pub struct UpdateResult {
     /// if it's out of date. Installers built with cargo-dist 0.12.0 or later
     /// will definitively use this value.
     pub install_prefix: Utf8PathBuf,
+    /// Whether the new version is a prerelease
+    pub prerelease: bool,
+    /// The release channel of the new version, if it's a prerelease; for
+    /// example, `beta` for `1.2.0-beta.1`
+    pub channel: Option<String>,
+}
+
+impl UpdateResult {
+    /// Describes installing `release` over `old_version` at `install_prefix`.
+    pub(crate) fn for_release(
+        old_version: Option<Version>,
+        release: &Release,
+        install_prefix: Utf8PathBuf,
+    ) -> UpdateResult {
+        UpdateResult {
+            old_version,
+            new_version: release.version.clone(),
+            new_version_tag: release.tag_name.to_owned(),
+            install_prefix,
+            prerelease: release.prerelease,
+            channel: channel_of(&release.version),
+        }
+    }
+}
+
+/// Describes whether the running executable is managed by the loaded
+/// install receipt, and is therefore eligible to be updated
+#[derive(Clone, Debug, PartialEq)]
+pub enum EligibilityResult {
+    /// The running executable is the one described by the install receipt
+    Eligible,
+    /// The running executable lives outside the install receipt's prefix,
+    /// for example because it was installed by a package manager
+    NotManaged {
+        /// The install prefix recorded in the receipt
+        receipt_root: Utf8PathBuf,
+        /// The install prefix of the running executable
+        exe_root: Utf8PathBuf,
+    },
+    /// The running executable lives in the install receipt's prefix, but
+    /// isn't one of the binaries the receipt installed
+    UnknownBinary {
+        /// The filename of the running executable
+        binary: String,
+        /// The binaries listed in the install receipt
+        binaries: Vec<String>,
+    },
+}
+
+/// How the running copy of an app was installed, as far as axoupdater can
+/// tell. See `AxoUpdater::installation_method`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InstallationMethod {
+    /// Installed by a cargo-dist shell or PowerShell installer, whose
+    /// install receipt describes the running executable; it can be updated
+    AxoInstaller,
+    /// Installed some other way, such as by a package manager, by
+    /// `cargo install` or by copying it into place; it shouldn't be updated
+    /// by axoupdater
+    Unmanaged,
+    /// The install receipt couldn't be read, or the running executable
+    /// couldn't be compared against it
+    Unknown,
+}
+
+/// Detailed information about whether an update is needed
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpdateNeeded {
+    /// A new version is available and should be installed
+    UpdateAvailable,
+    /// The requested version is already installed
+    UpToDate,
+    /// The running executable isn't managed by the install receipt, so it
+    /// can't be updated
+    NotEligible(EligibilityResult),
 }
 
+/// Explains why `AxoUpdater::run` didn't perform an update
+#[derive(Clone, Debug, PartialEq)]
+pub enum NoUpdateReason {
+    /// The requested version is already installed
+    UpToDate,
+    /// The running executable isn't managed by the install receipt, so it
+    /// can't be updated
+    NotEligible(EligibilityResult),
+    /// The release source doesn't have any releases matching the request
+    NoReleaseFound,
+}
+
+/// The result of `AxoUpdater::run`
+pub enum UpdateOutcome {
+    /// An update was installed
+    Updated(UpdateResult),
+    /// No update was performed
+    Skipped(NoUpdateReason),
+}
+
+impl From<UpdateOutcome> for Option<UpdateResult> {
+    /// Converts to the `Option` previously returned by `AxoUpdater::run`,
+    /// which is `None` if no update was performed.
+    fn from(outcome: UpdateOutcome) -> Self {
+        match outcome {
+            UpdateOutcome::Updated(result) => Some(result),
+            UpdateOutcome::Skipped(_) => None,
+        }
+    }
+}
+
+/// A callback invoked before or after an update is installed.
+/// See `AxoUpdater::set_pre_update_hook` and `AxoUpdater::set_post_update_hook`.
+pub type UpdateHook = Box<dyn Fn(&UpdateResult) -> Result<(), String> + Send + Sync>;
+
 /// Used to specify what version to upgrade to
-#[derive(Clone)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum UpdateRequest {
     /// Always update to the latest
     Latest,
@@ -59,6 +198,28 @@ pub enum UpdateRequest {
     SpecificVersion(String),
     /// Upgrade (or downgrade) to this specific tag
     SpecificTag(String),
+    /// Always update to the latest release on this channel. A channel is
+    /// identified by the start of the version's prerelease component; for
+    /// example, `1.2.0-beta.1` is on the `beta` channel. Releases without a
+    /// prerelease component are on the `stable` channel.
+    Channel(String),
+    /// Always update to the latest stable release with the same major
+    /// version as the one installed, so that updates never cross a major
+    /// version. Requires the current version to be known.
+    LatestWithinMajor,
+}
+
+/// How `AxoUpdater::run` installs the new version
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InstallMethod {
+    /// Download and run the release's shell or PowerShell installer
+    #[default]
+    Installer,
+    /// Download the archive built for this platform and copy its binaries
+    /// into the install prefix directly, without running an installer.
+    /// Requires the `archive_install` feature.
+    #[cfg(feature = "archive_install")]
+    Archive,
 }
 
 #[derive(Default)]
@@ -81,6 +242,10 @@ pub struct AxoUpdater {
     pub name: Option<String>,
     /// Information about where updates should be fetched from
     pub source: Option<ReleaseSource>,
+    /// Whether `source` was set explicitly, rather than read from the receipt
+    source_overridden: bool,
+    /// Sources to try, in order, if fetching from `source` fails
+    fallback_sources: Vec<ReleaseSource>,
     /// What version should be updated to
     version_specifier: UpdateRequest,
     /// Information about the latest release; used to determine if an update is needed
@@ -91,10 +256,18 @@ pub struct AxoUpdater {
     current_version_installed_by: Option<Provider>,
     /// Information about the install prefix of the previous version
     install_prefix: Option<Utf8PathBuf>,
+    /// The binaries installed by the previous version, if known
+    binaries: Vec<String>,
+    /// The name of the app's main binary, if it isn't the app's name
+    primary_binary: Option<String>,
     /// Whether to display the underlying installer's stdout
     print_installer_stdout: bool,
     /// Whether to display the underlying installer's stderr
     print_installer_stderr: bool,
+    /// Extra environment variables to set when running the installer
+    installer_env: HashMap<String, String>,
+    /// Extra arguments to pass to the installer
+    installer_args: Vec<String>,
     /// The path to the installer to use for the new version.
     /// If not specified, downloads the installer from the release source.
     installer_path: Option<Utf8PathBuf>,
@@ -104,8 +277,85 @@ pub struct AxoUpdater {
     /// When set to true, skips performing version checks and always assumes
     /// the software is out of date.
     always_update: bool,
+    /// When set to true, reinstalls even if the current version is already
+    /// the requested one, while still checking that this executable is
+    /// eligible for updates.
+    force_update: bool,
+    /// When set to true, treats a "latest" release that's older than the
+    /// installed version as an error
+    strict_latest: bool,
+    /// Whether to ask GitHub for its latest release instead of listing them all
+    prefer_api_latest: bool,
+    /// The oldest version this updater is allowed to install, if any
+    minimum_version: Option<Version>,
+    /// Versions which should never be installed
+    skip_versions: Vec<Version>,
+    /// The OS and architecture to select installers for, if not this host's
+    target_platform: Option<(String, String)>,
+    /// Whether to prefer installers for this machine's native architecture
+    /// when running under emulation
+    prefer_native_arch: bool,
+    /// Tags skipped during the last release lookup because they couldn't be parsed
+    skipped_tags: SkippedTags,
+    /// The latest release's ETag, shared with the GitHub backend so that the
+    /// release cache can revalidate it
+    latest_etag: LatestEtag,
+    /// Whether releases with non-version tags can be requested by tag
+    allow_non_semver_tags: bool,
+    /// The prefix identifying installer assets, if not `{app_name}-installer`
+    installer_pattern: Option<String>,
+    /// How to install the new version
+    #[cfg_attr(not(feature = "archive_install"), allow(dead_code))]
+    install_method: InstallMethod,
     /// Whether to modify the system path when installing
     modify_path: bool,
+    /// The timeout to apply to each network request, if any
+    request_timeout: Option<Duration>,
+    /// How long the installer may run before it's killed, if limited
+    installer_timeout: Option<Duration>,
+    /// The User-Agent to send with each network request. If not supplied,
+    /// a default based on axoupdater's version will be used.
+    user_agent: Option<String>,
+    /// Whether to ignore any proxy configured in the environment
+    no_proxy: bool,
+    /// Proxies to use in place of the environment's, if configured
+    proxy: Option<ProxyConfig>,
+    /// Additional headers to send with each network request
+    extra_headers: reqwest::header::HeaderMap,
+    /// An install prefix to use in place of the one from the install receipt
+    install_prefix_override: Option<Utf8PathBuf>,
+    /// Whether to remove the old binary after relocating the app
+    remove_old_binary_on_relocation: bool,
+    /// Where install receipts are loaded from, if not the filesystem
+    receipt_store: Option<Box<dyn ReceiptStore>>,
+    /// The directory under the platform's config directory that receipts
+    /// and other metadata are kept in, if not the app's name
+    config_subdir: Option<Utf8PathBuf>,
+    /// A directory to stage installers in, in place of the automatically chosen one
+    staging_dir_override: Option<PathBuf>,
+    /// Whether to resume interrupted downloads
+    resume_downloads: bool,
+    /// Where the installer was staged during the last update, if any
+    last_staging_choice: Mutex<Option<StagingChoice>>,
+    /// A callback to run before the installer is executed
+    pre_update_hook: Option<UpdateHook>,
+    /// A callback to run after the installer has successfully executed
+    post_update_hook: Option<UpdateHook>,
+    /// Used to verify the signatures of downloaded installers
+    signature_verifier: Option<Box<dyn SignatureVerifier>>,
+    /// Whether to check installers against the release's dist manifest
+    verify_dist_manifest: bool,
+    /// Downloaded installers smaller than this many bytes are rejected
+    min_installer_size: usize,
+    /// A backend to fetch releases from in place of the built-in ones
+    custom_backend: Option<Box<dyn ReleaseBackend>>,
+    /// Completes when long-running operations should be cancelled
+    cancellation: Option<cancel::Cancellation>,
+    /// How long cached release metadata remains valid, if caching is enabled
+    release_cache_ttl: Option<Duration>,
+    /// Arguments to run the installed binary with to verify an update, if
+    /// verification is enabled
+    post_update_verification: Option<Vec<String>>,
 }
 
 impl Default for AxoUpdater {
@@ -122,17 +372,57 @@ impl AxoUpdater {
         AxoUpdater {
             name: None,
             source: None,
+            source_overridden: false,
+            fallback_sources: vec![],
             version_specifier: UpdateRequest::Latest,
             requested_release: None,
             current_version: None,
             current_version_installed_by: None,
             install_prefix: None,
+            binaries: vec![],
+            primary_binary: None,
             print_installer_stdout: true,
             print_installer_stderr: true,
+            installer_env: HashMap::new(),
+            installer_args: vec![],
             installer_path: None,
             tokens: AuthorizationTokens::default(),
             always_update: false,
+            force_update: false,
+            strict_latest: false,
+            prefer_api_latest: true,
+            minimum_version: None,
+            skip_versions: vec![],
+            target_platform: None,
+            prefer_native_arch: false,
+            skipped_tags: SkippedTags::default(),
+            latest_etag: LatestEtag::default(),
+            allow_non_semver_tags: false,
+            installer_pattern: None,
+            install_method: InstallMethod::default(),
             modify_path: true,
+            request_timeout: None,
+            installer_timeout: None,
+            user_agent: None,
+            no_proxy: false,
+            proxy: None,
+            extra_headers: reqwest::header::HeaderMap::new(),
+            install_prefix_override: None,
+            remove_old_binary_on_relocation: false,
+            receipt_store: None,
+            config_subdir: None,
+            staging_dir_override: None,
+            resume_downloads: false,
+            last_staging_choice: Mutex::new(None),
+            pre_update_hook: None,
+            post_update_hook: None,
+            signature_verifier: None,
+            verify_dist_manifest: false,
+            min_installer_size: DEFAULT_MIN_INSTALLER_SIZE,
+            custom_backend: None,
+            cancellation: None,
+            release_cache_ttl: None,
+            post_update_verification: None,
         }
     }
 
@@ -140,18 +430,7 @@ impl AxoUpdater {
     pub fn new_for(app_name: &str) -> AxoUpdater {
         AxoUpdater {
             name: Some(app_name.to_owned()),
-            source: None,
-            version_specifier: UpdateRequest::Latest,
-            requested_release: None,
-            current_version: None,
-            current_version_installed_by: None,
-            install_prefix: None,
-            print_installer_stdout: true,
-            print_installer_stderr: true,
-            installer_path: None,
-            tokens: AuthorizationTokens::default(),
-            always_update: false,
-            modify_path: true,
+            ..Self::new()
         }
     }
 
@@ -168,29 +447,20 @@ impl AxoUpdater {
             return Err(AxoupdateError::UpdateSelf {});
         };
 
-        Ok(AxoUpdater {
-            name: Some(app_name.to_owned()),
-            source: None,
-            version_specifier: UpdateRequest::Latest,
-            requested_release: None,
-            current_version: None,
-            current_version_installed_by: None,
-            install_prefix: None,
-            print_installer_stdout: true,
-            print_installer_stderr: true,
-            installer_path: None,
-            tokens: AuthorizationTokens::default(),
-            always_update: false,
-            modify_path: true,
-        })
+        Ok(AxoUpdater::new_for(&app_name))
     }
 
     /// Explicitly configures the release source as an alternative to
     /// reading it from the install receipt. This can be useful for tasks
     /// which want to query the new version without actually performing an
-    /// upgrade.
+    /// upgrade, or to update from a different repository than the one the
+    /// app was installed from.
+    ///
+    /// A source set this way always takes precedence over the receipt's,
+    /// whether it's set before or after the receipt is loaded.
     pub fn set_release_source(&mut self, source: ReleaseSource) -> &mut AxoUpdater {
         self.source = Some(source);
+        self.source_overridden = true;
 
         self
     }
@@ -259,6 +529,22 @@ impl AxoUpdater {
         self
     }
 
+    /// Sets extra environment variables for the installer. These are applied
+    /// after the variables axoupdater sets itself, so they take precedence.
+    pub fn set_installer_env(&mut self, env: HashMap<String, String>) -> &mut AxoUpdater {
+        self.installer_env = env;
+
+        self
+    }
+
+    /// Sets extra arguments to pass to the installer, such as
+    /// `--no-modify-path`.
+    pub fn set_installer_args(&mut self, args: Vec<String>) -> &mut AxoUpdater {
+        self.installer_args = args;
+
+        self
+    }
+
     /// Configures AxoUpdater to use a specific installer for the new release
     /// instead of downloading it from the release source.
     pub fn configure_installer_path(&mut self, path: impl Into<Utf8PathBuf>) -> &mut AxoUpdater {
@@ -267,6 +553,28 @@ impl AxoUpdater {
         self
     }
 
+    /// Tells AxoUpdater which release the installer configured via
+    /// `configure_installer_path` belongs to. When combined with a local
+    /// installer, this allows an update to be performed without any network
+    /// access, since AxoUpdater doesn't need to look up the release.
+    /// This also configures the version specifier to request this tag.
+    pub fn set_known_release_metadata(&mut self, tag: &str, version: Version) -> &mut AxoUpdater {
+        self.version_specifier = UpdateRequest::SpecificTag(tag.to_owned());
+        self.requested_release = Some(Release {
+            tag_name: tag.to_owned(),
+            prerelease: !version.pre.is_empty(),
+            draft: false,
+            published_at: None,
+            body: None,
+            version,
+            name: tag.to_owned(),
+            url: String::new(),
+            assets: vec![],
+        });
+
+        self
+    }
+
     /// Configures AxoUpdater to use the installer from the new release.
     /// This is the default setting.
     pub fn use_release_installer(&mut self) -> &mut AxoUpdater {
@@ -283,6 +591,63 @@ impl AxoUpdater {
         self
     }
 
+    /// Configures AxoUpdater to install the new version to `prefix` instead
+    /// of the install prefix read from the install receipt. This can be used
+    /// to relocate an app during an update. The new prefix must be writable.
+    pub fn set_install_prefix(&mut self, prefix: impl Into<Utf8PathBuf>) -> &mut AxoUpdater {
+        self.install_prefix_override = Some(prefix.into());
+
+        self
+    }
+
+    /// Configures whether, after an update relocates the app with
+    /// `set_install_prefix`, the running executable left behind in the old
+    /// install prefix should be removed, so that a stale copy isn't left on
+    /// the user's PATH. This only applies on Unix, and only removes the
+    /// executable if it was in the install receipt's prefix, the new prefix
+    /// is a different directory, and the new copy of it was installed there.
+    /// Failing to remove it doesn't fail the update. Defaults to false.
+    pub fn set_remove_old_binary_on_relocation(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.remove_old_binary_on_relocation = setting;
+
+        self
+    }
+
+    /// Configures a callback to run after the new release has been resolved,
+    /// but before its installer is run. This can be used to prepare for the
+    /// update, for example by stopping a running service. If the callback
+    /// returns an error, the update is aborted with
+    /// `AxoupdateError::HookAborted`.
+    pub fn set_pre_update_hook(&mut self, hook: UpdateHook) -> &mut AxoUpdater {
+        self.pre_update_hook = Some(hook);
+
+        self
+    }
+
+    /// Configures a callback to run after the installer has completed
+    /// successfully. If the callback returns an error, it's returned as
+    /// `AxoupdateError::PostUpdateHookFailed`; note that the new version
+    /// will already have been installed at that point.
+    pub fn set_post_update_hook(&mut self, hook: UpdateHook) -> &mut AxoUpdater {
+        self.post_update_hook = Some(hook);
+
+        self
+    }
+
+    /// Configures axoupdater to check that the new version works after
+    /// installing it, by running the app's primary binary with `cmd_args`
+    /// and checking that it exits successfully. If `cmd_args` is empty,
+    /// `--version` is used, and the output is also checked to make sure it
+    /// mentions the new version.
+    /// If verification fails, `run` returns
+    /// `AxoupdateError::PostUpdateVerificationFailed`; note that the new
+    /// version will already have been installed at that point.
+    pub fn set_post_update_verification(&mut self, cmd_args: Vec<String>) -> &mut AxoUpdater {
+        self.post_update_verification = Some(cmd_args);
+
+        self
+    }
+
     /// Configures axoupdater's update strategy, replacing whatever was
     /// previously configured with the strategy in `version_specifier`.
     pub fn configure_version_specifier(
@@ -295,12 +660,276 @@ impl AxoUpdater {
     }
 
     /// Always upgrade, including when already running the latest version or when the current version isn't known
+    ///
+    /// This also skips checking that the running executable is managed by
+    /// the install receipt. To reinstall the requested release while still
+    /// checking that, use `set_force_update`.
     pub fn always_update(&mut self, setting: bool) -> &mut AxoUpdater {
         self.always_update = setting;
 
         self
     }
 
+    /// Configures a timeout to apply to each network request made by the
+    /// updater, including fetching release information and downloading the
+    /// installer. A request which exceeds this timeout will fail with
+    /// `AxoupdateError::Reqwest`. By default, no timeout is applied.
+    pub fn set_request_timeout(&mut self, timeout: Duration) -> &mut AxoUpdater {
+        self.request_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Configures how long the installer may run before it's killed. When
+    /// the timeout expires, the installer and any processes it started are
+    /// stopped, the running executable is restored if it had been moved
+    /// aside, and `run` fails with `AxoupdateError::InstallerTimedOut`.
+    /// By default, the installer may run indefinitely.
+    pub fn set_installer_timeout(&mut self, timeout: Duration) -> &mut AxoUpdater {
+        self.installer_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Configures the User-Agent header sent with each network request.
+    /// This can be useful for API gateways which require a specific
+    /// User-Agent. GitHub requires a User-Agent to be set, so an empty
+    /// value will be rejected.
+    pub fn set_user_agent(&mut self, user_agent: &str) -> AxoupdateResult<&mut AxoUpdater> {
+        if user_agent.is_empty() {
+            return Err(AxoupdateError::NotConfigured {
+                missing_field: "user_agent".to_owned(),
+            });
+        }
+        self.user_agent = Some(user_agent.to_owned());
+
+        Ok(self)
+    }
+
+    /// Configures additional headers to send with each request to GitHub
+    /// and each asset download, such as the authentication header required
+    /// by an internal mirror or API gateway. A `User-Agent` header here
+    /// replaces the one set by `set_user_agent`; headers which axoupdater
+    /// sets on individual requests, like `Accept` and the GitHub token's
+    /// `Authorization`, take precedence over these.
+    ///
+    /// Like `set_no_proxy`, this doesn't apply to requests to Axo Releases.
+    pub fn set_extra_headers(&mut self, headers: reqwest::header::HeaderMap) -> &mut AxoUpdater {
+        self.extra_headers = headers;
+
+        self
+    }
+
+    /// Configures whether network requests should ignore any proxy
+    /// configured in the environment. By default, requests are sent through
+    /// the proxies named by `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY`, or
+    /// their lowercase forms, except for hosts listed in `NO_PROXY`; or
+    /// through the proxies configured with `set_proxy`.
+    ///
+    /// Requests to Axo Releases are made by gazenot's own HTTP client, which
+    /// reads the same environment variables but can't be configured by
+    /// axoupdater; this setting doesn't apply to them.
+    pub fn set_no_proxy(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.no_proxy = setting;
+
+        self
+    }
+
+    /// Builds the HTTP client used for all requests made by this updater.
+    pub(crate) fn http_client(&self) -> AxoupdateResult<reqwest::Client> {
+        let user_agent = self.user_agent.clone().unwrap_or_else(default_user_agent);
+        let mut builder = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .default_headers(self.extra_headers.clone());
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        } else if let Some(proxy) = &self.proxy {
+            builder = proxy.apply(builder)?;
+        }
+
+        build_http_client(builder)
+    }
+
+    /// Builds a request to download `asset`.
+    ///
+    /// When a GitHub token is set, the asset is fetched from its API
+    /// endpoint with the token attached, which also works for private
+    /// repositories. GitHub responds by redirecting to a presigned storage
+    /// URL on another host; reqwest drops the `Authorization` header when
+    /// following redirects across hosts, which that storage requires since
+    /// it rejects requests carrying both kinds of credentials.
+    pub(crate) fn asset_request(
+        &self,
+        client: &reqwest::Client,
+        asset: &Asset,
+    ) -> reqwest::RequestBuilder {
+        let is_github = matches!(
+            &self.source,
+            Some(ReleaseSource {
+                release_type: ReleaseSourceType::GitHub,
+                ..
+            })
+        );
+        let request = match &self.tokens.github {
+            Some(token) if is_github => client.get(&asset.url).bearer_auth(token),
+            _ => client.get(&asset.browser_download_url),
+        };
+
+        request.header(reqwest::header::ACCEPT, "application/octet-stream")
+    }
+
+    /// Always run the installer for the requested release, even if that
+    /// version is already installed. This can be used to repair a broken
+    /// installation. Unlike `always_update`, this still checks that the
+    /// running executable is managed by the install receipt, and won't
+    /// update copies which aren't.
+    pub fn set_force_update(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.force_update = setting;
+
+        self
+    }
+
+    /// Configures whether a "latest" release which is older than the
+    /// installed version should be reported as an error. This usually
+    /// means that the wrong release was marked as latest, for example after
+    /// a re-tag. By default, this is treated as the app being up to date.
+    pub fn set_strict_latest(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.strict_latest = setting;
+
+        self
+    }
+
+    /// Configures whether the latest release on GitHub is looked up with
+    /// GitHub's "latest release" API. This is the default, and takes a
+    /// single request. However, GitHub's idea of the latest release doesn't
+    /// always match axoupdater's, for example when maintainers mark an older
+    /// release as latest. When disabled, every release is listed and the
+    /// highest stable version is picked instead; this is always consistent
+    /// with the other version specifiers, but takes one request for every
+    /// hundred releases, which counts against the API rate limit.
+    pub fn set_prefer_api_latest(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.prefer_api_latest = setting;
+
+        self
+    }
+
+    /// Sets the oldest version this updater is allowed to install. If the
+    /// resolved release is older than this, `run` fails with
+    /// `AxoupdateError::VersionBelowMinimum` instead of installing it.
+    /// This guards against accidental downgrades, for example from a typo
+    /// when requesting a specific version.
+    pub fn set_minimum_version(&mut self, version: Version) -> &mut AxoUpdater {
+        self.minimum_version = Some(version);
+
+        self
+    }
+
+    /// Excludes `versions` from updates, for example to route around a
+    /// known-broken release. When looking for the latest release, skipped
+    /// versions are passed over in favour of the newest one that isn't
+    /// skipped; requesting a skipped version specifically fails with
+    /// `AxoupdateError::VersionSkipped`.
+    pub fn set_skip_versions(&mut self, versions: Vec<Version>) -> &mut AxoUpdater {
+        self.skip_versions = versions;
+
+        self
+    }
+
+    /// Configures whether releases whose tags aren't versions, such as
+    /// commit SHAs or `nightly`, can be installed by requesting their tag
+    /// with `UpdateRequest::SpecificTag`. Such releases are given a
+    /// placeholder version of `0.0.0-sha.{tag}`. This is currently only
+    /// supported for releases hosted on GitHub; listing releases still
+    /// skips them.
+    pub fn set_allow_non_semver_tags(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.allow_non_semver_tags = setting;
+
+        self
+    }
+
+    /// Configures which release assets are treated as this app's installers.
+    ///
+    /// By default, these are the assets cargo-dist produces: the generic
+    /// `{app_name}-installer.sh` and `{app_name}-installer.ps1`, and
+    /// platform-specific ones such as
+    /// `{app_name}-x86_64-unknown-linux-gnu-installer.sh`. Releases without
+    /// an installer are skipped when looking for updates, so apps whose
+    /// installers are named differently should set this.
+    ///
+    /// When set, any asset whose name starts with `pattern` is an
+    /// installer. `{pattern}.sh` or `{pattern}.ps1` is used as the generic
+    /// installer, and an installer whose name also contains this platform's
+    /// target triple is preferred over it.
+    pub fn set_installer_asset_pattern(&mut self, pattern: String) -> &mut AxoUpdater {
+        self.installer_pattern = Some(pattern);
+
+        self
+    }
+
+    /// Configures how `run` installs the new version. By default, the
+    /// release's installer is downloaded and run; with the `archive_install`
+    /// feature, `InstallMethod::Archive` can be used to install from the
+    /// platform's archive instead, for systems where the installer can't or
+    /// shouldn't be run.
+    pub fn set_install_method(&mut self, method: InstallMethod) -> &mut AxoUpdater {
+        self.install_method = method;
+
+        self
+    }
+
+    /// Overrides the OS and architecture used to select which installer to
+    /// download, named as in `std::env::consts` (for example, `"linux"` and
+    /// `"aarch64"`). This only affects installer selection, so it's useful
+    /// for inspecting which installer another platform would receive via
+    /// `resolved_installer_url`; `run` refuses to execute an installer
+    /// selected for a platform other than this one.
+    pub fn set_target_platform(&mut self, os: String, arch: String) -> &mut AxoUpdater {
+        self.target_platform = Some((os, arch));
+
+        self
+    }
+
+    /// Configures whether to prefer installers built for this machine's
+    /// native architecture over the one this binary was built for, when
+    /// it's running under emulation. For example, an x86_64 build running
+    /// under Rosetta 2 on Apple Silicon will update to the aarch64 build,
+    /// if the release has an installer or archive specifically for it,
+    /// moving users onto the native build. Installers for the emulated
+    /// architecture are still used if there isn't one. Defaults to false.
+    pub fn set_prefer_native_arch(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.prefer_native_arch = setting;
+
+        self
+    }
+
+    /// Ensures that any overridden target platform matches this host, since
+    /// installers for other platforms can't be run here.
+    fn check_target_platform(&self) -> AxoupdateResult<()> {
+        match &self.target_platform {
+            Some((os, arch)) if os != env::consts::OS || arch != env::consts::ARCH => {
+                Err(AxoupdateError::TargetPlatformMismatch {
+                    os: os.to_owned(),
+                    arch: arch.to_owned(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `version` against the configured minimum version, if any.
+    fn check_minimum_version(&self, version: &Version) -> AxoupdateResult<()> {
+        match &self.minimum_version {
+            Some(minimum) if version < minimum => Err(AxoupdateError::VersionBelowMinimum {
+                requested: version.to_string(),
+                minimum: minimum.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Determines if an update is needed by querying the newest version from
     /// the location specified in `source`.
     /// This includes a blocking network call, so it may be slow.
@@ -312,13 +941,32 @@ impl AxoUpdater {
     /// that the install receipt is for. In the case that the executable comes
     /// from a different source, it will return before the network call for a
     /// new version.
+    /// The release fetched by the first call is reused by later calls and by
+    /// `run`; call `refresh` to check for a newer one.
     pub async fn is_update_needed(&mut self) -> AxoupdateResult<bool> {
         if self.always_update {
             return Ok(true);
         }
 
-        if !self.check_receipt_is_for_this_executable()? {
-            return Ok(false);
+        Ok(self.is_update_needed_detailed().await? == UpdateNeeded::UpdateAvailable)
+    }
+
+    /// Like `is_update_needed`, but distinguishes between the executable
+    /// already being up to date and the executable not being managed by the
+    /// install receipt. The latter is common for copies installed by a
+    /// package manager, which shouldn't update themselves.
+    pub async fn is_update_needed_detailed(&mut self) -> AxoupdateResult<UpdateNeeded> {
+        if self.always_update {
+            return Ok(UpdateNeeded::UpdateAvailable);
+        }
+
+        let eligibility = self.check_eligibility()?;
+        if eligibility != EligibilityResult::Eligible {
+            return Ok(UpdateNeeded::NotEligible(eligibility));
+        }
+
+        if self.force_update {
+            return Ok(UpdateNeeded::UpdateAvailable);
         }
 
         let Some(current_version) = self.current_version.to_owned() else {
@@ -327,37 +975,52 @@ impl AxoUpdater {
             });
         };
 
-        let release = match &self.requested_release {
-            Some(r) => r,
-            None => {
-                self.fetch_release().await?;
-                self.requested_release.as_ref().unwrap()
-            }
-        };
+        if self.requested_release.is_none() {
+            self.fetch_release().await?;
+        }
+        let release = self.requested_release.as_ref().unwrap();
+
+        if self.newer_version_available(&current_version, &release.version)? {
+            Ok(UpdateNeeded::UpdateAvailable)
+        } else {
+            Ok(UpdateNeeded::UpToDate)
+        }
+    }
+
+    /// Compares the installed version to the one that was fetched.
+    /// Build metadata is ignored, so re-publishing a version with different
+    /// build metadata isn't treated as an update.
+    fn newer_version_available(&self, current: &Version, new: &Version) -> AxoupdateResult<bool> {
+        let current = &without_build_metadata(current);
+        let new = &without_build_metadata(new);
 
         // If we're doing "latest" semantics we need to check cur < new
         // If we're doing "specific" semantics we need to check cur != new
-        let conclusion = match self.version_specifier {
+        match self.version_specifier {
             UpdateRequest::Latest | UpdateRequest::LatestMaybePrerelease => {
-                current_version < release.version
-            }
-            UpdateRequest::SpecificVersion(_) | UpdateRequest::SpecificTag(_) => {
-                current_version != release.version
+                if self.strict_latest && new < current {
+                    return Err(AxoupdateError::LatestIsOlder {
+                        installed: current.to_string(),
+                        latest: new.to_string(),
+                    });
+                }
+                Ok(current < new)
             }
-        };
-        Ok(conclusion)
+            UpdateRequest::Channel(_) | UpdateRequest::LatestWithinMajor => Ok(current < new),
+            UpdateRequest::SpecificVersion(_) | UpdateRequest::SpecificTag(_) => Ok(current != new),
+        }
     }
 
     #[cfg(feature = "blocking")]
     /// Identical to Axoupdater::is_update_needed(), but performed synchronously.
     pub fn is_update_needed_sync(&mut self) -> AxoupdateResult<bool> {
-        tokio::runtime::Builder::new_current_thread()
-            .worker_threads(1)
-            .max_blocking_threads(128)
-            .enable_all()
-            .build()
-            .expect("Initializing tokio runtime failed")
-            .block_on(self.is_update_needed())
+        block_on(self.is_update_needed())?
+    }
+
+    #[cfg(feature = "blocking")]
+    /// Identical to Axoupdater::is_update_needed_detailed(), but performed synchronously.
+    pub fn is_update_needed_detailed_sync(&mut self) -> AxoupdateResult<UpdateNeeded> {
+        block_on(self.is_update_needed_detailed())?
     }
 
     /// Returns the root of the install prefix, stripping the final `/bin`
@@ -387,6 +1050,58 @@ impl AxoUpdater {
         Ok(install_root)
     }
 
+    /// Sets the name of the app's main binary, for apps whose binary isn't
+    /// named after the app itself. This is the binary that's run to verify
+    /// updates.
+    pub fn set_primary_binary(&mut self, name: String) -> &mut AxoUpdater {
+        self.primary_binary = Some(name);
+
+        self
+    }
+
+    /// Returns the name of the app's main binary, without any `.exe`
+    /// extension. Unless one was set with `set_primary_binary`, this is the
+    /// binary named after the app if the install receipt lists one, or else
+    /// the first binary it lists.
+    pub fn primary_binary(&self) -> Option<String> {
+        if let Some(binary) = &self.primary_binary {
+            return Some(binary.to_owned());
+        }
+
+        let stem = |binary: &String| binary.strip_suffix(".exe").unwrap_or(binary).to_owned();
+        self.binaries
+            .iter()
+            .find(|binary| Some(stem(*binary)) == self.name)
+            .or(self.binaries.first())
+            .map(stem)
+    }
+
+    /// Returns the paths of the binaries listed in the install receipt which
+    /// exist on disk. Depending on how the app was installed, binaries may
+    /// be located either in the install prefix itself or in its `bin`
+    /// directory; both are checked. If an install prefix override was set,
+    /// binaries are looked for there instead.
+    pub fn installed_binary_paths(&self) -> AxoupdateResult<Vec<Utf8PathBuf>> {
+        let root = self.installed_binaries_root()?;
+
+        let paths = self
+            .binaries
+            .iter()
+            .filter_map(|binary| find_installed_binary(&root, binary))
+            .collect();
+
+        Ok(paths)
+    }
+
+    /// Returns the install prefix the app's binaries are in: the install
+    /// prefix override, if one was set, or the receipt's install prefix.
+    pub(crate) fn installed_binaries_root(&self) -> AxoupdateResult<Utf8PathBuf> {
+        match &self.install_prefix_override {
+            Some(prefix) => Ok(prefix.to_owned()),
+            None => self.install_prefix_root(),
+        }
+    }
+
     /// Returns a normalized version of install_prefix_root, for comparison
     fn install_prefix_root_normalized(&self) -> AxoupdateResult<Utf8PathBuf> {
         let raw_root = self.install_prefix_root()?;
@@ -405,12 +1120,33 @@ impl AxoUpdater {
     }
 
     /// Attempts to perform an update. The return value specifies whether an
-    /// update was actually performed or not; false indicates "no update was
-    /// needed", while an error indicates that an update couldn't be performed
-    /// due to an error.
-    pub async fn run(&mut self) -> AxoupdateResult<Option<UpdateResult>> {
-        if !self.is_update_needed().await? {
-            return Ok(None);
+    /// update was actually performed or not, and if not, why not; an error
+    /// indicates that an update couldn't be performed due to an error.
+    /// If a release has already been fetched, for example by
+    /// `is_update_needed`, that release is installed without checking again;
+    /// call `refresh` first to look for a newer one.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(app = ?self.name)))]
+    pub async fn run(&mut self) -> AxoupdateResult<UpdateOutcome> {
+        self.check_target_platform()?;
+        // The installer may replace this, so note where it was beforehand
+        let previous_exe = env::current_exe().and_then(|path| path.canonicalize()).ok();
+
+        match self.is_update_needed_detailed().await {
+            Ok(UpdateNeeded::UpdateAvailable) => {}
+            Ok(UpdateNeeded::UpToDate) => {
+                return Ok(UpdateOutcome::Skipped(NoUpdateReason::UpToDate))
+            }
+            Ok(UpdateNeeded::NotEligible(eligibility)) => {
+                return Ok(UpdateOutcome::Skipped(NoUpdateReason::NotEligible(
+                    eligibility,
+                )))
+            }
+            // Only treat this as "nothing to update to" if the release
+            // source actually answered; HTTP failures are still errors.
+            Err(AxoupdateError::NoStableReleases { status: None, .. }) => {
+                return Ok(UpdateOutcome::Skipped(NoUpdateReason::NoReleaseFound))
+            }
+            Err(e) => return Err(e),
         }
 
         let release = match &self.requested_release {
@@ -420,7 +1156,31 @@ impl AxoUpdater {
                 self.requested_release.as_ref().unwrap()
             }
         };
-        let tempdir = TempDir::new()?;
+        self.check_minimum_version(&release.version)?;
+
+        let install_prefix = if let Some(prefix) = &self.install_prefix_override {
+            prefix.to_owned()
+        } else {
+            self.install_prefix_root()?
+        };
+        // Fail before downloading anything, rather than leaving the
+        // installer to fail with a less helpful error
+        if !is_dir_writable(&install_prefix) {
+            return Err(AxoupdateError::InstallPrefixNotWritable {
+                path: install_prefix,
+            });
+        }
+
+        let tempdir = self.select_installer_tempdir(Some(install_prefix.as_std_path()))?;
+
+        #[cfg(feature = "archive_install")]
+        if self.install_method == InstallMethod::Archive {
+            let outcome = self
+                .install_from_archive(release, &install_prefix, &tempdir)
+                .await?;
+            self.remove_relocated_binary(previous_exe.as_deref(), &install_prefix);
+            return Ok(outcome);
+        }
 
         // If we've been given an installer path to use, skip downloading and
         // install from that.
@@ -429,69 +1189,69 @@ impl AxoUpdater {
         // Otherwise, proceed with downloading the installer from the release
         // we just looked up.
         } else {
-            let app_name = self.name.clone().unwrap_or_default();
-            let installer_url = match env::consts::OS {
-                "macos" | "linux" => release
-                    .assets
-                    .iter()
-                    .find(|asset| asset.name == format!("{app_name}-installer.sh")),
-                "windows" => release
-                    .assets
-                    .iter()
-                    .find(|asset| asset.name == format!("{app_name}-installer.ps1")),
-                _ => unreachable!(),
-            };
-
-            let installer_url = if let Some(installer_url) = installer_url {
-                installer_url
-            } else {
+            let Some(installer_url) = self.select_installer_asset(release) else {
                 return Err(AxoupdateError::NoInstallerForPackage {});
             };
 
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                url = %installer_url.browser_download_url,
+                "selected installer"
+            );
+
             let extension = if cfg!(windows) { ".ps1" } else { ".sh" };
 
             let installer_path =
                 Utf8PathBuf::try_from(tempdir.path().join(format!("installer{extension}")))?;
 
-            #[cfg(unix)]
-            {
-                let installer_file = File::create(&installer_path)?;
-                let mut perms = installer_file.metadata()?.permissions();
-                perms.set_mode(0o744);
-                installer_file.set_permissions(perms)?;
-            }
+            #[cfg(feature = "tracing")]
+            tracing::debug!("downloading installer");
+            let client = self.http_client()?;
+            // Streamed straight to disk as raw bytes, rather than decoded as
+            // text, so that installers in other encodings are written out
+            // unchanged
+            let _size = self
+                .download_asset_to(&client, installer_url, installer_path.as_std_path())
+                .await?;
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(bytes = _size, "downloaded installer");
+
+            check_installer_file(&installer_path, cfg!(windows), self.min_installer_size)?;
 
-            let client = axoasset::reqwest::Client::new();
-            let download = client
-                .get(&installer_url.browser_download_url)
-                .header(
-                    axoasset::reqwest::header::ACCEPT,
-                    "application/octet-stream",
-                )
-                .send()
-                .await?
-                .text()
+            self.verify_installer_checksum(&client, release, installer_url, &installer_path)
+                .await?;
+            self.verify_installer_signature(&client, release, installer_url, &installer_path)
                 .await?;
 
-            LocalAsset::write_new_all(&download, &installer_path)?;
+            #[cfg(unix)]
+            std::fs::set_permissions(&installer_path, std::fs::Permissions::from_mode(0o744))?;
 
             installer_path
         };
 
+        let result = UpdateResult::for_release(
+            self.current_version.clone(),
+            release,
+            install_prefix.clone(),
+        );
+
+        // Give the caller a chance to veto or prepare for the update before
+        // anything on disk has been touched.
+        if let Some(hook) = &self.pre_update_hook {
+            hook(&result).map_err(|message| AxoupdateError::HookAborted { message })?;
+        }
+
+        // Once the installer has started, it's allowed to finish
+        self.check_cancelled()?;
+
         // Before we update, rename ourselves to a temporary name.
         // This is necessary because Windows won't let an actively-running
         // executable be overwritten.
         // If the update fails, we'll move it back to where it was before
         // we began the update process.
         let to_restore = if cfg!(target_family = "windows") {
-            let old_filename = std::env::current_exe()?;
-
-            let mut new_filename = old_filename.as_os_str().to_os_string();
-            // Filename follows the pattern set here: https://docs.rs/self-replace/1.5.0/self_replace/#implementation
-            new_filename.push(OsStr::new(".previous.exe"));
-            std::fs::rename(&old_filename, &new_filename)?;
-
-            Some((new_filename, old_filename))
+            Some(move_self_aside(&std::env::current_exe()?)?)
         } else {
             None
         };
@@ -509,18 +1269,22 @@ impl AxoUpdater {
             command.arg("-ExecutionPolicy").arg("ByPass");
             command.arg(&installer_path);
         }
-        if self.print_installer_stdout {
-            command.stdout(Stdio::inherit());
-        }
-        if self.print_installer_stderr {
-            command.stderr(Stdio::inherit());
-        }
+        command.args(&self.installer_args);
+        command.stdout(if self.print_installer_stdout {
+            Stdio::inherit()
+        } else {
+            Stdio::piped()
+        });
+        command.stderr(if self.print_installer_stderr {
+            Stdio::inherit()
+        } else {
+            Stdio::piped()
+        });
         command.check(false);
         // On Windows, fixes a bug that occurs if the parent process is
         // PowerShell Core.
         // https://github.com/PowerShell/PowerShell/issues/18530
         command.env_remove("PSModulePath");
-        let install_prefix = self.install_prefix_root()?;
         // Forces the generated installer to install to exactly this path,
         // regardless of how it's configured to install.
         command.env("CARGO_DIST_FORCE_INSTALL_DIR", &install_prefix);
@@ -538,13 +1302,24 @@ impl AxoUpdater {
             command.env(app_specific_modify_path, "1");
         }
 
-        let result = command.output();
+        command.envs(&self.installer_env);
+
+        let output = match self.installer_timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map_err(AxoupdateError::from),
+        };
+
+        #[cfg(feature = "tracing")]
+        match &output {
+            Ok(output) => tracing::info!(status = ?output.status.code(), "installer exited"),
+            Err(e) => tracing::warn!(error = %e, "failed to run installer"),
+        }
 
         let failed;
         let stdout;
         let stderr;
         let statuscode;
-        if let Ok(output) = &result {
+        if let Ok(output) = &output {
             failed = !output.status.success();
             stdout = if output.stdout.is_empty() {
                 None
@@ -566,7 +1341,7 @@ impl AxoUpdater {
 
         if let Some((ourselves, old_path)) = to_restore {
             if failed {
-                std::fs::rename(ourselves, old_path)?;
+                restore_self(&ourselves, &old_path)?;
             } else {
                 #[cfg(windows)]
                 self_replace::self_delete_at(&ourselves)
@@ -576,7 +1351,7 @@ impl AxoUpdater {
 
         // Return the original AxoprocessError if we failed to launch
         // the command at all
-        result?;
+        output?;
 
         // Otherwise return a more specific error with status code and
         // stdout/err. Note that this stdout/stderr will be None if the
@@ -589,29 +1364,208 @@ impl AxoUpdater {
             });
         }
 
-        let result = UpdateResult {
-            old_version: self.current_version.clone(),
-            new_version: release.version.clone(),
-            new_version_tag: release.tag_name.to_owned(),
-            install_prefix,
+        self.verify_installed_update(&result.new_version)?;
+        self.remove_relocated_binary(previous_exe.as_deref(), &install_prefix);
+
+        if let Some(hook) = &self.post_update_hook {
+            hook(&result).map_err(|message| AxoupdateError::PostUpdateHookFailed { message })?;
+        }
+
+        Ok(UpdateOutcome::Updated(result))
+    }
+
+    /// After an update which relocated the app, removes `previous_exe`, the
+    /// executable that was running before the update, if configured to.
+    /// This is deliberately cautious: the executable must have been in the
+    /// install receipt's prefix, the app must have been installed to a
+    /// different directory, and a new copy of the executable must exist
+    /// there.
+    fn remove_relocated_binary(
+        &self,
+        previous_exe: Option<&std::path::Path>,
+        install_prefix: &Utf8Path,
+    ) {
+        if !cfg!(unix)
+            || !self.remove_old_binary_on_relocation
+            || self.install_prefix_override.is_none()
+        {
+            return;
+        }
+        let Some(previous_exe) = previous_exe else {
+            return;
+        };
+        let (Ok(old_root), Ok(new_root)) = (
+            self.install_prefix_root_normalized(),
+            install_prefix.canonicalize(),
+        ) else {
+            return;
+        };
+        if new_root == old_root.as_std_path()
+            || !previous_exe.starts_with(&old_root)
+            || previous_exe.starts_with(&new_root)
+        {
+            return;
+        }
+        let Some(new_exe) = previous_exe
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| find_installed_binary(install_prefix, name))
+        else {
+            return;
         };
+        // Don't remove the new copy if it's really the old one, for
+        // example because it's a symlink to it
+        if new_exe.canonicalize().ok().as_deref() == Some(previous_exe) {
+            return;
+        }
 
-        Ok(Some(result))
+        let _removed = std::fs::remove_file(previous_exe);
+        #[cfg(feature = "tracing")]
+        match &_removed {
+            Ok(()) => {
+                tracing::info!(path = %previous_exe.display(), "removed the binary left in the previous install prefix")
+            }
+            Err(e) => {
+                tracing::warn!(path = %previous_exe.display(), error = %e, "couldn't remove the binary left in the previous install prefix")
+            }
+        }
+    }
+
+    /// Runs the post-update verification command against the newly
+    /// installed binary, if one was configured.
+    fn verify_installed_update(&self, new_version: &Version) -> AxoupdateResult<()> {
+        let Some(args) = &self.post_update_verification else {
+            return Ok(());
+        };
+        let (args, check_version) = if args.is_empty() {
+            (vec!["--version".to_owned()], true)
+        } else {
+            (args.to_owned(), false)
+        };
+
+        let primary_binary = self.primary_binary().unwrap_or_default();
+        let binaries = self.installed_binary_paths()?;
+        let Some(binary) = binaries
+            .iter()
+            .find(|path| path.file_stem() == Some(primary_binary.as_str()))
+        else {
+            return Err(AxoupdateError::PostUpdateVerificationFailed {
+                status: None,
+                message: format!("the installed binary {primary_binary} wasn't found"),
+            });
+        };
+
+        let mut command = Cmd::new(binary, "verify the new version");
+        command.args(&args);
+        command.check(false);
+        let output = command.output()?;
+
+        if !output.status.success() {
+            return Err(AxoupdateError::PostUpdateVerificationFailed {
+                status: output.status.code(),
+                message: format!("{binary} exited unsuccessfully"),
+            });
+        }
+        if check_version
+            && !String::from_utf8_lossy(&output.stdout).contains(&new_version.to_string())
+        {
+            return Err(AxoupdateError::PostUpdateVerificationFailed {
+                status: output.status.code(),
+                message: format!("{binary} didn't report the expected version, {new_version}"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Selects the installer asset from `release` that's appropriate for
+    /// the target platform, if any. An installer built for the platform's
+    /// target triple is preferred over the generic installer.
+    pub(crate) fn select_installer_asset<'a>(&self, release: &'a Release) -> Option<&'a Asset> {
+        let app_name = self.name.clone().unwrap_or_default();
+        match &self.target_platform {
+            Some((os, arch)) => find_installer(
+                release,
+                &app_name,
+                self.installer_pattern.as_deref(),
+                os,
+                triple_for(os, arch, "").as_deref(),
+            ),
+            None => {
+                let native = self
+                    .prefer_native_arch
+                    .then(native_triple)
+                    .flatten()
+                    .and_then(|triple| self.find_installer_for_triple(release, &app_name, &triple));
+                native.or_else(|| {
+                    find_installer(
+                        release,
+                        &app_name,
+                        self.installer_pattern.as_deref(),
+                        env::consts::OS,
+                        target_triple().as_deref(),
+                    )
+                })
+            }
+        }
+    }
+
+    /// Finds the installer built specifically for `triple`, ignoring the
+    /// generic installer.
+    fn find_installer_for_triple<'a>(
+        &self,
+        release: &'a Release,
+        app_name: &str,
+        triple: &str,
+    ) -> Option<&'a Asset> {
+        let installer = find_installer(
+            release,
+            app_name,
+            self.installer_pattern.as_deref(),
+            env::consts::OS,
+            Some(triple),
+        )?;
+        if !installer.name.contains(triple) {
+            return None;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            installer = %installer.name,
+            "running under emulation; preferring the installer for the native architecture"
+        );
+
+        Some(installer)
+    }
+
+    /// Returns the URL of the installer that would be downloaded to perform
+    /// an update, without downloading it. If the release hasn't been fetched
+    /// yet, this will query the release source.
+    pub async fn resolved_installer_url(&mut self) -> AxoupdateResult<String> {
+        let release = match &self.requested_release {
+            Some(r) => r,
+            None => {
+                self.fetch_release().await?;
+                self.requested_release.as_ref().unwrap()
+            }
+        };
+
+        let Some(installer) = self.select_installer_asset(release) else {
+            return Err(AxoupdateError::NoInstallerForPackage {});
+        };
+
+        Ok(installer.browser_download_url.to_owned())
     }
 
     #[cfg(feature = "blocking")]
     /// Identical to Axoupdater::run(), but performed synchronously.
-    pub fn run_sync(&mut self) -> AxoupdateResult<Option<UpdateResult>> {
-        tokio::runtime::Builder::new_current_thread()
-            .worker_threads(1)
-            .max_blocking_threads(128)
-            .enable_all()
-            .build()
-            .expect("Initializing tokio runtime failed")
-            .block_on(self.run())
+    pub fn run_sync(&mut self) -> AxoupdateResult<UpdateOutcome> {
+        block_on(self.run())?
     }
 
     /// Queries for new releases and then returns the detected version.
+    /// To look up a version without configuring an updater, see
+    /// `latest_version`.
     pub async fn query_new_version(&mut self) -> AxoupdateResult<Option<&Version>> {
         self.fetch_release().await?;
 
@@ -621,6 +1575,91 @@ impl AxoUpdater {
             Ok(None)
         }
     }
+
+    #[cfg(feature = "blocking")]
+    /// Identical to Axoupdater::query_new_version(), but performed synchronously.
+    pub fn query_new_version_sync(&mut self) -> AxoupdateResult<Option<&Version>> {
+        block_on(self.query_new_version())?
+    }
+
+    /// Forgets the release fetched by a previous check, so that the next
+    /// call to `is_update_needed`, `query_new_version` or `run` fetches it
+    /// again. Long-lived updaters that poll for new releases need to call
+    /// this between checks. Release metadata cached on disk with
+    /// `enable_release_cache` is still reused until it expires; use
+    /// `invalidate_cache` to discard it too.
+    pub fn refresh(&mut self) -> &mut AxoUpdater {
+        self.requested_release = None;
+
+        self
+    }
+
+    /// Returns the installed version, if it's known. It's read from the
+    /// install receipt, or can be set with `set_current_version`.
+    pub fn current_version(&self) -> Option<&Version> {
+        self.current_version.as_ref()
+    }
+
+    /// Returns the release that will be installed, if one has been fetched
+    /// by `query_new_version`, `is_update_needed` or `run`, or set manually.
+    pub fn requested_release(&self) -> Option<&Release> {
+        self.requested_release.as_ref()
+    }
+
+    #[cfg(feature = "blocking")]
+    /// Identical to Axoupdater::peek_latest_release(), but performed synchronously.
+    pub fn peek_latest_release_sync(&self) -> AxoupdateResult<Release> {
+        block_on(self.peek_latest_release())?
+    }
+}
+
+#[cfg(feature = "blocking")]
+/// The tokio runtime shared by every blocking method, created on first use
+static BLOCKING_RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+
+#[cfg(feature = "blocking")]
+/// The default maximum number of threads the blocking runtime may use for
+/// blocking operations
+const DEFAULT_MAX_BLOCKING_THREADS: usize = 128;
+
+#[cfg(feature = "blocking")]
+/// Configures the maximum number of threads the blocking methods' tokio
+/// runtime may use for blocking operations; the default is 128.
+///
+/// The runtime is created once and shared by every blocking method, so
+/// this must be called before any of them. Afterwards, it fails with
+/// `AxoupdateError::BlockingRuntimeInitialized`.
+pub fn set_max_blocking_threads(threads: usize) -> AxoupdateResult<()> {
+    let runtime = build_blocking_runtime(threads)?;
+    BLOCKING_RUNTIME
+        .set(runtime)
+        .map_err(|_| AxoupdateError::BlockingRuntimeInitialized {})
+}
+
+#[cfg(feature = "blocking")]
+fn build_blocking_runtime(max_blocking_threads: usize) -> std::io::Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .worker_threads(1)
+        .max_blocking_threads(max_blocking_threads)
+        .enable_all()
+        .build()
+}
+
+#[cfg(feature = "blocking")]
+/// Runs `future` to completion on the shared single-threaded tokio runtime.
+/// Fails if called from within another tokio runtime, where blocking on a
+/// future would panic.
+fn block_on<F: std::future::Future>(future: F) -> AxoupdateResult<F::Output> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(AxoupdateError::BlockingInAsyncContext {});
+    }
+
+    Ok(BLOCKING_RUNTIME
+        .get_or_init(|| {
+            build_blocking_runtime(DEFAULT_MAX_BLOCKING_THREADS)
+                .expect("Initializing tokio runtime failed")
+        })
+        .block_on(future))
 }
 
 fn get_app_name() -> Option<String> {
@@ -642,8 +1681,177 @@ pub fn app_name_to_env_var(app_name: &str) -> String {
     app_name.to_ascii_uppercase().replace('-', "_")
 }
 
+/// Returns a copy of `version` with its build metadata removed. The semver
+/// crate takes build metadata into account when comparing versions, even
+/// though the spec says it shouldn't affect precedence.
+fn without_build_metadata(version: &Version) -> Version {
+    let mut version = version.clone();
+    version.build = semver::BuildMetadata::EMPTY;
+
+    version
+}
+
+/// Renames the executable at `exe` aside so the installer can write a new
+/// one in its place, returning the path it was moved to along with the path
+/// it was moved from.
+///
+/// If `exe` is a symlink, such as a shim created by a package manager, the
+/// file it points to is the one the installer will replace, so that's what
+/// gets moved. The new name is in the same directory as the file being
+/// moved, so this never has to move it across volumes.
+fn move_self_aside(
+    exe: &std::path::Path,
+) -> AxoupdateResult<(std::path::PathBuf, std::path::PathBuf)> {
+    let is_symlink = exe
+        .symlink_metadata()
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    let old_path = if is_symlink {
+        exe.canonicalize()?
+    } else {
+        exe.to_path_buf()
+    };
+
+    let mut new_path = old_path.as_os_str().to_os_string();
+    // Filename follows the pattern set here: https://docs.rs/self-replace/1.5.0/self_replace/#implementation
+    new_path.push(OsStr::new(".previous.exe"));
+    let new_path = std::path::PathBuf::from(new_path);
+
+    std::fs::rename(&old_path, &new_path).map_err(|source| AxoupdateError::SelfMoveFailed {
+        from: old_path.clone(),
+        to: new_path.clone(),
+        source,
+    })?;
+
+    Ok((new_path, old_path))
+}
+
+/// After a failed update, moves the copy of ourselves that was renamed aside
+/// before running the installer back to `old_path`. If the installer got as
+/// far as writing a new executable to `old_path` before failing, that one is
+/// left in place and our copy is left where it is.
+fn restore_self(ourselves: &std::path::Path, old_path: &std::path::Path) -> AxoupdateResult<()> {
+    if old_path.exists() {
+        return Ok(());
+    }
+
+    std::fs::rename(ourselves, old_path).map_err(|_| AxoupdateError::SelfRestoreFailed {
+        path: ourselves.to_path_buf(),
+    })
+}
+
+/// Runs `command` to completion like `Cmd::output`, but kills it and any
+/// processes it started if it's still running after `timeout`.
+fn output_with_timeout(
+    command: &mut Cmd,
+    timeout: Duration,
+) -> AxoupdateResult<std::process::Output> {
+    // Run in a new process group so that the whole tree can be killed
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command.inner, 0);
+
+    let mut child = command.spawn()?;
+    // Drain the pipes as we go, so a chatty installer can't fill them and stall
+    fn drain<R: std::io::Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> Option<std::thread::JoinHandle<Vec<u8>>> {
+        pipe.map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut buf = vec![];
+                let _ = pipe.read_to_end(&mut buf);
+                buf
+            })
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = std::time::Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if std::time::Instant::now() >= deadline {
+            kill_process_tree(&mut child);
+            return Err(AxoupdateError::InstallerTimedOut { timeout });
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    let collect = |handle: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        handle
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+    Ok(std::process::Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Kills `child` along with any processes it started.
+fn kill_process_tree(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    let _ = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .status();
+    #[cfg(windows)]
+    let _ = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .status();
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// The User-Agent requests are sent with unless the app sets its own.
+pub(crate) fn default_user_agent() -> String {
+    format!("axoupdate/{VERSION}")
+}
+
+/// Builds an HTTP client from `builder`, using the TLS backend selected by
+/// this crate's features.
+pub(crate) fn build_http_client(
+    builder: reqwest::ClientBuilder,
+) -> AxoupdateResult<reqwest::Client> {
+    // rustls is the default, so native-tls wins if it was asked for too
+    #[cfg(feature = "native-tls")]
+    let builder = builder.use_native_tls();
+    #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
+    let builder = builder.use_rustls_tls();
+
+    Ok(builder.build()?)
+}
+
+/// Checks whether new files can be created in `path`, or in its nearest
+/// existing ancestor if it doesn't exist yet.
+fn is_dir_writable(path: &Utf8Path) -> bool {
+    let mut candidate = Some(path);
+    while let Some(dir) = candidate {
+        if dir.exists() {
+            return tempfile::NamedTempFile::new_in(dir).is_ok();
+        }
+        candidate = dir.parent();
+    }
+
+    false
+}
+
+/// Returns true if `name` is a `bin` directory. Windows paths are
+/// case-insensitive, so `Bin` and `BIN` are also accepted there.
+pub(crate) fn is_bin_dir(name: Option<&str>) -> bool {
+    match name {
+        Some(name) if cfg!(windows) => name.eq_ignore_ascii_case("bin"),
+        Some(name) => name == "bin",
+        None => false,
+    }
+}
+
 fn root_without_bin(path: &Utf8PathBuf) -> Utf8PathBuf {
-    if path.file_name() == Some("bin") {
+    // Note that file_name() ignores any trailing separator, so both
+    // `prefix/bin` and `prefix/bin/` are handled here.
+    if is_bin_dir(path.file_name()) {
         if let Some(parent) = path.parent() {
             return parent.to_path_buf();
         }
@@ -652,11 +1860,39 @@ fn root_without_bin(path: &Utf8PathBuf) -> Utf8PathBuf {
     path.to_owned()
 }
 
+/// Finds `binary` in the install prefix `root`, checking both the prefix's
+/// `bin` directory and the prefix itself.
+pub(crate) fn find_installed_binary(root: &Utf8Path, binary: &str) -> Option<Utf8PathBuf> {
+    let filename = if cfg!(windows) && !binary.ends_with(".exe") {
+        format!("{binary}.exe")
+    } else {
+        binary.to_owned()
+    };
+
+    [root.join("bin").join(&filename), root.join(&filename)]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
 #[cfg(test)]
 mod tests {
-    use std::path::{Path, PathBuf};
+    use std::{
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    use axoasset::reqwest;
+    use httpmock::prelude::*;
+    use serial_test::serial;
 
-    use crate::AxoUpdater;
+    use camino::{Utf8Path, Utf8PathBuf};
+
+    use crate::{
+        is_dir_writable, move_self_aside, restore_self, root_without_bin,
+        test::fixtures::{asset, release_with_assets},
+        Asset, AxoUpdater, AxoupdateError, EligibilityResult, UpdateNeeded, UpdateOutcome,
+        UpdateRequest, UpdateResult, Version,
+    };
 
     #[test]
     fn test_installer_path_str() {
@@ -711,4 +1947,700 @@ mod tests {
         path.push("/tmp");
         updater.set_install_dir(&path.to_string_lossy());
     }
+
+    #[test]
+    fn test_known_release_metadata() {
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater
+            .configure_installer_path("/tmp/installer.sh")
+            .set_known_release_metadata("v1.2.0-beta.1", Version::parse("1.2.0-beta.1").unwrap());
+
+        let release = updater.requested_release().unwrap();
+        assert_eq!(release.tag_name, "v1.2.0-beta.1");
+        assert!(release.prerelease);
+        assert!(matches!(
+            updater.version_specifier,
+            UpdateRequest::SpecificTag(ref tag) if tag == "v1.2.0-beta.1"
+        ));
+    }
+
+    #[test]
+    fn test_current_version() {
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        assert!(updater.current_version().is_none());
+        assert!(updater.requested_release().is_none());
+
+        updater
+            .set_current_version(Version::parse("1.1.0").unwrap())
+            .unwrap();
+        assert_eq!(updater.current_version().unwrap().to_string(), "1.1.0");
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater
+            .configure_installer_path("/tmp/installer.sh")
+            .set_known_release_metadata("v1.2.0", Version::parse("1.2.0").unwrap());
+        assert!(updater.requested_release().is_some());
+
+        updater.refresh();
+        assert!(updater.requested_release().is_none());
+    }
+
+    #[test]
+    fn test_strict_latest() {
+        let installed = Version::parse("1.2.0").unwrap();
+        let older = Version::parse("1.1.0").unwrap();
+        let newer = Version::parse("1.3.0").unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        assert!(!updater.newer_version_available(&installed, &older).unwrap());
+        assert!(updater.newer_version_available(&installed, &newer).unwrap());
+
+        updater.set_strict_latest(true);
+        assert!(matches!(
+            updater.newer_version_available(&installed, &older),
+            Err(AxoupdateError::LatestIsOlder { .. })
+        ));
+        assert!(!updater
+            .newer_version_available(&installed, &installed)
+            .unwrap());
+
+        // Requesting a specific, older version is still allowed
+        updater.configure_version_specifier(UpdateRequest::SpecificVersion("1.1.0".to_owned()));
+        assert!(updater.newer_version_available(&installed, &older).unwrap());
+    }
+
+    #[test]
+    fn test_build_metadata_ignored() {
+        let installed = Version::parse("1.0.0+a").unwrap();
+        let rebuilt = Version::parse("1.0.0+b").unwrap();
+        let newer = Version::parse("1.0.1+a").unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        assert!(!updater
+            .newer_version_available(&installed, &rebuilt)
+            .unwrap());
+        assert!(updater.newer_version_available(&installed, &newer).unwrap());
+
+        updater.configure_version_specifier(UpdateRequest::SpecificVersion("1.0.0+b".to_owned()));
+        assert!(!updater
+            .newer_version_available(&installed, &rebuilt)
+            .unwrap());
+        assert!(updater.newer_version_available(&installed, &newer).unwrap());
+    }
+
+    #[test]
+    fn test_minimum_version() {
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        let old = Version::parse("0.2.115").unwrap();
+        // Downgrades are allowed if no minimum is set
+        assert!(updater.check_minimum_version(&old).is_ok());
+
+        updater.set_minimum_version(Version::parse("0.2.116").unwrap());
+        assert!(matches!(
+            updater.check_minimum_version(&old),
+            Err(AxoupdateError::VersionBelowMinimum { .. })
+        ));
+        assert!(updater
+            .check_minimum_version(&Version::parse("0.2.116").unwrap())
+            .is_ok());
+        assert!(updater
+            .check_minimum_version(&Version::parse("0.3.0").unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_select_installer_asset() {
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                asset("axolotlsay-installer.sh"),
+                asset("axolotlsay-installer.ps1"),
+                asset("axolotlsay-x86_64-unknown-linux-gnu.tar.gz"),
+            ],
+        );
+
+        let updater = AxoUpdater::new_for("axolotlsay");
+        let selected = updater.select_installer_asset(&release).unwrap();
+        if cfg!(windows) {
+            assert_eq!(selected.name, "axolotlsay-installer.ps1");
+        } else {
+            assert_eq!(selected.name, "axolotlsay-installer.sh");
+        }
+    }
+
+    #[test]
+    fn test_select_installer_asset_for_target_platform() {
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                asset("axolotlsay-installer.sh"),
+                asset("axolotlsay-installer.ps1"),
+                asset("axolotlsay-aarch64-unknown-linux-gnu-installer.sh"),
+            ],
+        );
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_target_platform("windows".to_owned(), "x86_64".to_owned());
+        let selected = updater.select_installer_asset(&release).unwrap();
+        assert_eq!(selected.name, "axolotlsay-installer.ps1");
+
+        updater.set_target_platform("linux".to_owned(), "aarch64".to_owned());
+        let selected = updater.select_installer_asset(&release).unwrap();
+        assert_eq!(
+            selected.name,
+            "axolotlsay-aarch64-unknown-linux-gnu-installer.sh"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_installer_timeout() {
+        let mut command = axoprocess::Cmd::new("sh", "run slow installer");
+        command.arg("-c").arg("sleep 30 & wait");
+        command.stdout(std::process::Stdio::piped());
+
+        let started = std::time::Instant::now();
+        let result = crate::output_with_timeout(&mut command, Duration::from_millis(200));
+        assert!(matches!(
+            result,
+            Err(AxoupdateError::InstallerTimedOut { .. })
+        ));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let mut command = axoprocess::Cmd::new("sh", "run quick installer");
+        command.arg("-c").arg("echo done");
+        command.stdout(std::process::Stdio::piped());
+        let output = crate::output_with_timeout(&mut command, Duration::from_secs(10)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"done\n");
+    }
+
+    #[test]
+    fn test_update_result_serialization() {
+        let result = UpdateResult {
+            old_version: Some(Version::parse("1.0.0").unwrap()),
+            new_version: Version::parse("1.1.0-beta.1").unwrap(),
+            new_version_tag: "v1.1.0-beta.1".to_owned(),
+            install_prefix: Utf8PathBuf::from("/home/axo/.cargo"),
+            prerelease: true,
+            channel: Some("beta".to_owned()),
+        };
+
+        let json = axoasset::serde_json::to_value(&result).unwrap();
+        assert_eq!(json["install_prefix"], "/home/axo/.cargo");
+        assert_eq!(json["old_version"], "1.0.0");
+        assert_eq!(json["new_version"], "1.1.0-beta.1");
+
+        let parsed: UpdateResult = axoasset::serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.new_version, result.new_version);
+        assert_eq!(parsed.install_prefix, result.install_prefix);
+
+        let request = UpdateRequest::SpecificTag("v1.0.0".to_owned());
+        let json = axoasset::serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            axoasset::serde_json::from_str::<UpdateRequest>(&json).unwrap(),
+            request
+        );
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_asset_request_strips_auth_on_redirect() {
+        let api = MockServer::start_async().await;
+        let storage = MockServer::start_async().await;
+        let redirect = api
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/assets/1")
+                    .header("authorization", "Bearer secret")
+                    .header("accept", "application/octet-stream");
+                then.status(302)
+                    .header("location", storage.url("/signed?sig=abc"));
+            })
+            .await;
+        // Presigned storage URLs reject requests that also carry a token
+        let rejected = storage
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/signed")
+                    .header_exists("authorization");
+                then.status(400);
+            })
+            .await;
+        let signed = storage
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/signed")
+                    .header_missing("authorization");
+                then.status(200).body("#!/bin/sh\n");
+            })
+            .await;
+
+        let asset = Asset {
+            url: api.url("/assets/1"),
+            ..asset("axolotlsay-installer.sh")
+        };
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_release_source(crate::ReleaseSource {
+            release_type: crate::ReleaseSourceType::GitHub,
+            owner: "axodotdev".to_owned(),
+            name: "axolotlsay".to_owned(),
+            app_name: "axolotlsay".to_owned(),
+        });
+        updater.set_github_token("secret");
+        let client = updater.http_client().unwrap();
+
+        let response = updater.asset_request(&client, &asset).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "#!/bin/sh\n");
+        redirect.assert_async().await;
+        signed.assert_async().await;
+        rejected.assert_hits_async(0).await;
+    }
+
+    #[tokio::test]
+    async fn test_run_refuses_other_target_platform() {
+        let other_os = if cfg!(windows) { "linux" } else { "windows" };
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_target_platform(other_os.to_owned(), std::env::consts::ARCH.to_owned());
+
+        assert!(matches!(
+            updater.run().await,
+            Err(AxoupdateError::TargetPlatformMismatch { .. })
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_runtime_is_shared() {
+        assert_eq!(crate::block_on(async { 1 }).unwrap(), 1);
+        assert_eq!(crate::block_on(async { 2 }).unwrap(), 2);
+        // The runtime already exists, so it's too late to configure it
+        assert!(matches!(
+            crate::set_max_blocking_threads(4),
+            Err(AxoupdateError::BlockingRuntimeInitialized {})
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[tokio::test]
+    async fn test_blocking_in_async_context() {
+        assert!(matches!(
+            crate::block_on(async {}),
+            Err(AxoupdateError::BlockingInAsyncContext {})
+        ));
+    }
+
+    #[test]
+    fn test_root_without_bin() {
+        assert_eq!(
+            root_without_bin(&Utf8PathBuf::from("/home/me/.cargo/bin")),
+            Utf8PathBuf::from("/home/me/.cargo")
+        );
+        assert_eq!(
+            root_without_bin(&Utf8PathBuf::from("/home/me/.cargo/bin/")),
+            Utf8PathBuf::from("/home/me/.cargo")
+        );
+        assert_eq!(
+            root_without_bin(&Utf8PathBuf::from("/home/me/.cargo")),
+            Utf8PathBuf::from("/home/me/.cargo")
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_root_without_bin_windows() {
+        assert_eq!(
+            root_without_bin(&Utf8PathBuf::from(r"C:\Users\me\App\Bin")),
+            Utf8PathBuf::from(r"C:\Users\me\App")
+        );
+        assert_eq!(
+            root_without_bin(&Utf8PathBuf::from(r"C:\Users\me\App\bin\")),
+            Utf8PathBuf::from(r"C:\Users\me\App")
+        );
+    }
+
+    #[test]
+    fn test_is_dir_writable() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        assert!(is_dir_writable(root));
+        // Directories which don't exist yet are checked via their parent
+        assert!(is_dir_writable(&root.join("new").join("prefix")));
+    }
+
+    #[test]
+    fn test_installed_binary_paths() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        let exe = |name: &str| format!("{name}{}", std::env::consts::EXE_SUFFIX);
+        std::fs::create_dir(root.join("bin")).unwrap();
+        std::fs::write(root.join("bin").join(exe("axolotlsay")), "").unwrap();
+        std::fs::write(root.join(exe("axolotlsay-helper")), "").unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.install_prefix = Some(root.to_owned());
+        updater.binaries = vec![
+            "axolotlsay".to_owned(),
+            "axolotlsay-helper".to_owned(),
+            "missing".to_owned(),
+        ];
+
+        assert_eq!(
+            updater.installed_binary_paths().unwrap(),
+            vec![
+                root.join("bin").join(exe("axolotlsay")),
+                root.join(exe("axolotlsay-helper")),
+            ]
+        );
+
+        // Binaries are looked for in the install prefix override instead,
+        // when there is one
+        let relocated_dir = tempfile::TempDir::new().unwrap();
+        let relocated = Utf8Path::from_path(relocated_dir.path()).unwrap();
+        std::fs::write(relocated.join(exe("axolotlsay")), "").unwrap();
+        updater.set_install_prefix(relocated);
+        assert_eq!(updater.installed_binaries_root().unwrap(), relocated);
+        assert_eq!(
+            updater.installed_binary_paths().unwrap(),
+            vec![relocated.join(exe("axolotlsay"))]
+        );
+    }
+
+    #[test]
+    fn test_primary_binary() {
+        let mut updater = AxoUpdater::new_for("my-tool");
+        assert_eq!(updater.primary_binary(), None);
+
+        updater.binaries = vec!["mt".to_owned(), "mt-helper".to_owned()];
+        assert_eq!(updater.primary_binary().as_deref(), Some("mt"));
+
+        updater.binaries = vec!["mt-helper.exe".to_owned(), "my-tool.exe".to_owned()];
+        assert_eq!(updater.primary_binary().as_deref(), Some("my-tool"));
+
+        updater.set_primary_binary("mt-helper".to_owned());
+        assert_eq!(updater.primary_binary().as_deref(), Some("mt-helper"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_post_update_verification() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        let binary = root.join("axolotlsay");
+        let write_binary = |script: &str| {
+            std::fs::write(&binary, format!("#!/bin/sh\n{script}\n")).unwrap();
+            std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        };
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.install_prefix = Some(root.to_owned());
+        updater.binaries = vec!["axolotlsay".to_owned()];
+        let new_version = Version::parse("1.2.0").unwrap();
+
+        // Disabled by default
+        assert!(updater.verify_installed_update(&new_version).is_ok());
+
+        updater.set_post_update_verification(vec![]);
+        write_binary("echo axolotlsay 1.2.0");
+        assert!(updater.verify_installed_update(&new_version).is_ok());
+
+        write_binary("echo axolotlsay 1.1.0");
+        assert!(updater.verify_installed_update(&new_version).is_err());
+
+        // Custom arguments only check the exit status
+        updater.set_post_update_verification(vec!["--help".to_owned()]);
+        assert!(updater.verify_installed_update(&new_version).is_ok());
+
+        write_binary("exit 3");
+        assert!(matches!(
+            updater.verify_installed_update(&new_version),
+            Err(AxoupdateError::PostUpdateVerificationFailed {
+                status: Some(3),
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_force_update_checks_eligibility() {
+        // This test executable wasn't installed into this prefix
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.install_prefix = Some(Utf8Path::from_path(tempdir.path()).unwrap().to_owned());
+        updater.set_force_update(true);
+
+        assert!(matches!(
+            updater.is_update_needed_detailed().await.unwrap(),
+            UpdateNeeded::NotEligible(EligibilityResult::NotManaged { .. })
+        ));
+
+        // always_update skips the check entirely
+        updater.always_update(true);
+        assert_eq!(
+            updater.is_update_needed_detailed().await.unwrap(),
+            UpdateNeeded::UpdateAvailable
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_update_hooks() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::sync::{Arc, Mutex};
+
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        let installer = root.join("installer.sh");
+        std::fs::write(
+            &installer,
+            "#!/bin/sh\ntouch \"$CARGO_DIST_FORCE_INSTALL_DIR/installed\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&installer, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let prefix = root.join("prefix");
+        std::fs::create_dir(&prefix).unwrap();
+        let installed = prefix.join("installed");
+
+        let calls = Arc::new(Mutex::new(vec![]));
+        let updater = |pre_hook_result: Result<(), String>| {
+            let mut updater = AxoUpdater::new_for("axolotlsay");
+            let (pre_calls, post_calls) = (calls.clone(), calls.clone());
+            updater
+                .always_update(true)
+                .configure_installer_path(&installer)
+                .set_known_release_metadata("v1.2.0", Version::parse("1.2.0").unwrap())
+                .set_install_prefix(&prefix)
+                .set_pre_update_hook(Box::new(move |result| {
+                    pre_calls
+                        .lock()
+                        .unwrap()
+                        .push(format!("pre {}", result.new_version));
+                    pre_hook_result.clone()
+                }))
+                .set_post_update_hook(Box::new(move |result| {
+                    post_calls
+                        .lock()
+                        .unwrap()
+                        .push(format!("post {}", result.new_version));
+                    Ok(())
+                }));
+            updater
+        };
+
+        // A failing pre-update hook stops the installer from running
+        let aborted = updater(Err("busy".to_owned())).run().await;
+        assert!(matches!(
+            aborted,
+            Err(AxoupdateError::HookAborted { ref message }) if message == "busy"
+        ));
+        assert!(!installed.exists());
+        assert_eq!(*calls.lock().unwrap(), vec!["pre 1.2.0"]);
+
+        calls.lock().unwrap().clear();
+        let outcome = updater(Ok(())).run().await.unwrap();
+        assert!(matches!(outcome, UpdateOutcome::Updated(_)));
+        assert!(installed.exists());
+        assert_eq!(*calls.lock().unwrap(), vec!["pre 1.2.0", "post 1.2.0"]);
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_request_timeout() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/slow");
+                then.status(200).delay(Duration::from_secs(5));
+            })
+            .await;
+
+        let mut updater = AxoUpdater::new();
+        updater.set_request_timeout(Duration::from_millis(100));
+        let result = updater
+            .http_client()
+            .unwrap()
+            .get(server.url("/slow"))
+            .send()
+            .await;
+
+        assert!(result.unwrap_err().is_timeout());
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_extra_headers() {
+        let server = MockServer::start_async().await;
+        let default_agent = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/default")
+                    .header("X-Internal-Auth", "secret")
+                    .header("User-Agent", format!("axoupdate/{}", crate::VERSION));
+                then.status(200);
+            })
+            .await;
+        let custom_agent = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/custom")
+                    .header("X-Internal-Auth", "secret")
+                    .header("User-Agent", "mirror-client");
+                then.status(200);
+            })
+            .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("X-Internal-Auth", "secret".parse().unwrap());
+        let mut updater = AxoUpdater::new();
+        updater.set_extra_headers(headers.clone());
+        let client = updater.http_client().unwrap();
+        client.get(server.url("/default")).send().await.unwrap();
+        default_agent.assert_async().await;
+
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            "mirror-client".parse().unwrap(),
+        );
+        updater.set_extra_headers(headers);
+        let client = updater.http_client().unwrap();
+        client.get(server.url("/custom")).send().await.unwrap();
+        custom_agent.assert_async().await;
+    }
+
+    #[test]
+    fn test_restore_self() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let old_path = tempdir.path().join("app.exe");
+        let ourselves = tempdir.path().join("app.exe.previous.exe");
+
+        // The installer failed before touching anything; we're moved back
+        std::fs::write(&ourselves, "old").unwrap();
+        restore_self(&ourselves, &old_path).unwrap();
+        assert_eq!(std::fs::read_to_string(&old_path).unwrap(), "old");
+        assert!(!ourselves.exists());
+
+        // The installer wrote a new binary, then failed; it's left in place
+        std::fs::rename(&old_path, &ourselves).unwrap();
+        std::fs::write(&old_path, "new").unwrap();
+        restore_self(&ourselves, &old_path).unwrap();
+        assert_eq!(std::fs::read_to_string(&old_path).unwrap(), "new");
+        assert!(ourselves.exists());
+
+        // Our copy has gone missing, so there's nothing to restore
+        std::fs::remove_file(&old_path).unwrap();
+        std::fs::remove_file(&ourselves).unwrap();
+        assert!(matches!(
+            restore_self(&ourselves, &old_path),
+            Err(AxoupdateError::SelfRestoreFailed { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_relocated_binary() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8PathBuf::try_from(tempdir.path().canonicalize().unwrap()).unwrap();
+        let (old_prefix, new_prefix) = (root.join("old"), root.join("new"));
+        let old_exe = old_prefix.join("bin").join("axolotlsay");
+        let new_exe = new_prefix.join("bin").join("axolotlsay");
+        for exe in [&old_exe, &new_exe] {
+            std::fs::create_dir_all(exe.parent().unwrap()).unwrap();
+            std::fs::write(exe, "").unwrap();
+        }
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_install_dir(&old_prefix);
+        // Nothing happens unless it's been asked for
+        updater.set_install_prefix(&new_prefix);
+        updater.remove_relocated_binary(Some(old_exe.as_std_path()), &new_prefix);
+        assert!(old_exe.exists());
+
+        // ...or if the app wasn't moved anywhere
+        updater.set_remove_old_binary_on_relocation(true);
+        updater.set_install_prefix(&old_prefix);
+        updater.remove_relocated_binary(Some(old_exe.as_std_path()), &old_prefix);
+        assert!(old_exe.exists());
+
+        // ...or if the new copy is missing
+        std::fs::remove_file(&new_exe).unwrap();
+        updater.set_install_prefix(&new_prefix);
+        updater.remove_relocated_binary(Some(old_exe.as_std_path()), &new_prefix);
+        assert!(old_exe.exists());
+
+        std::fs::write(&new_exe, "").unwrap();
+        updater.remove_relocated_binary(Some(old_exe.as_std_path()), &new_prefix);
+        assert!(!old_exe.exists());
+        assert!(new_exe.exists());
+    }
+
+    #[test]
+    fn test_move_self_aside() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let exe = tempdir.path().join("app.exe");
+        std::fs::write(&exe, "old").unwrap();
+
+        let (ourselves, old_path) = move_self_aside(&exe).unwrap();
+        assert_eq!(old_path, exe);
+        assert_eq!(ourselves, tempdir.path().join("app.exe.previous.exe"));
+        assert!(!exe.exists());
+        restore_self(&ourselves, &old_path).unwrap();
+
+        // A symlinked shim moves the real executable, not the link
+        #[cfg(unix)]
+        {
+            let shim = tempdir.path().join("shim");
+            std::os::unix::fs::symlink(&exe, &shim).unwrap();
+            let (ourselves, old_path) = move_self_aside(&shim).unwrap();
+            assert_eq!(old_path, exe.canonicalize().unwrap());
+            assert_eq!(std::fs::read_to_string(&ourselves).unwrap(), "old");
+            assert!(shim.symlink_metadata().is_ok());
+        }
+
+        let missing = tempdir.path().join("missing.exe");
+        assert!(matches!(
+            move_self_aside(&missing),
+            Err(AxoupdateError::SelfMoveFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_no_proxy() {
+        let proxy = MockServer::start_async().await;
+        let proxied = proxy
+            .mock_async(|when, then| {
+                when.method(GET).header("host", "axoupdater.invalid");
+                then.status(200);
+            })
+            .await;
+
+        let mut updater = AxoUpdater::new();
+        updater.set_proxy(crate::ProxyConfig {
+            http: Some(proxy.base_url()),
+            ..Default::default()
+        });
+        let send = |client: reqwest::Client| async move {
+            client.get("http://axoupdater.invalid/").send().await
+        };
+        let with_proxy = send(updater.http_client().unwrap()).await;
+        // Even explicitly configured proxies are skipped
+        let without_proxy = send(updater.set_no_proxy(true).http_client().unwrap()).await;
+
+        assert!(with_proxy.is_ok());
+        assert!(without_proxy.is_err());
+        proxied.assert_hits_async(1).await;
+    }
+
+    #[test]
+    fn test_empty_user_agent() {
+        let mut updater = AxoUpdater::new();
+        assert!(updater.set_user_agent("").is_err());
+        assert!(updater.set_user_agent("my-app/1.0").is_ok());
+    }
 }
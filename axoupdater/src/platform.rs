@@ -0,0 +1,245 @@
+//! Information about the platform axoupdater is running on
+
+use std::env;
+
+use crate::release::{Asset, Release};
+
+/// The target environment axoupdater was built for, where it affects the
+/// target triple
+const TARGET_ENV: &str = if cfg!(target_env = "musl") {
+    "musl"
+} else if cfg!(target_env = "gnu") {
+    "gnu"
+} else {
+    ""
+};
+
+/// Returns the target triple axoupdater was built for, in the form used by
+/// cargo-dist to name platform-specific artifacts, such as
+/// `x86_64-unknown-linux-gnu`. Returns `None` for platforms cargo-dist
+/// doesn't build for.
+pub fn target_triple() -> Option<String> {
+    triple_for(env::consts::OS, env::consts::ARCH, TARGET_ENV)
+}
+
+/// Returns the target triple for `os` and `arch`, named as in
+/// `std::env::consts`. `target_env` picks between the triples which differ
+/// only by libc or toolchain; if it's empty, glibc or MSVC is assumed.
+pub(crate) fn triple_for(os: &str, arch: &str, target_env: &str) -> Option<String> {
+    let arch = match arch {
+        "x86" => "i686",
+        "arm" => "armv7",
+        arch @ ("x86_64" | "aarch64" | "powerpc64" | "riscv64" | "s390x") => arch,
+        _ => return None,
+    };
+    let platform = match os {
+        "linux" if target_env == "musl" => "unknown-linux-musl",
+        "linux" if arch == "armv7" => "unknown-linux-gnueabihf",
+        "linux" => "unknown-linux-gnu",
+        "macos" => "apple-darwin",
+        "windows" if target_env == "gnu" => "pc-windows-gnu",
+        "windows" => "pc-windows-msvc",
+        _ => return None,
+    };
+
+    Some(format!("{arch}-{platform}"))
+}
+
+/// The architectures cargo-dist builds for, as named in target triples
+const KNOWN_ARCHES: &[&str] = &[
+    "aarch64",
+    "arm",
+    "armv7",
+    "i686",
+    "loongarch64",
+    "powerpc64",
+    "powerpc64le",
+    "riscv64",
+    "riscv64gc",
+    "s390x",
+    "x86_64",
+];
+
+/// The platforms cargo-dist builds for, as named in target triples after
+/// the architecture
+const KNOWN_PLATFORMS: &[&str] = &[
+    "apple-darwin",
+    "pc-windows-gnu",
+    "pc-windows-msvc",
+    "unknown-freebsd",
+    "unknown-illumos",
+    "unknown-linux-gnu",
+    "unknown-linux-gnueabihf",
+    "unknown-linux-musl",
+    "unknown-linux-musleabihf",
+    "unknown-netbsd",
+];
+
+/// Returns whether `triple` is a target triple cargo-dist builds for, such
+/// as `x86_64-unknown-linux-gnu`.
+pub(crate) fn is_known_triple(triple: &str) -> bool {
+    triple.split_once('-').is_some_and(|(arch, platform)| {
+        KNOWN_ARCHES.contains(&arch) && KNOWN_PLATFORMS.contains(&platform)
+    })
+}
+
+/// Returns the target triple for this machine's native architecture, if
+/// axoupdater is running under emulation for a different one. Currently
+/// this only detects x86_64 builds running under Rosetta 2 on Apple
+/// Silicon, which can run aarch64 builds natively.
+pub(crate) fn native_triple() -> Option<String> {
+    native_triple_for(env::consts::OS, env::consts::ARCH, is_translated())
+}
+
+/// Returns the native target triple for a process built for `os` and
+/// `arch`, given whether it's being translated by Rosetta 2.
+fn native_triple_for(os: &str, arch: &str, translated: bool) -> Option<String> {
+    match (os, arch) {
+        ("macos", "x86_64") if translated => triple_for("macos", "aarch64", ""),
+        _ => None,
+    }
+}
+
+/// Returns whether this process is being translated by Rosetta 2. The
+/// `sysctl.proc_translated` sysctl is 1 for translated processes and 0 for
+/// native ones; it doesn't exist on Intel Macs, which can't translate.
+/// This can't change while the process is running, so it's only checked
+/// once.
+#[cfg(target_os = "macos")]
+fn is_translated() -> bool {
+    static TRANSLATED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+    *TRANSLATED.get_or_init(|| {
+        std::process::Command::new("/usr/sbin/sysctl")
+            .args(["-n", "sysctl.proc_translated"])
+            .stderr(std::process::Stdio::null())
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_translated() -> bool {
+    false
+}
+
+/// Finds the installer for the platform described by `os` and `triple`
+/// among `release`'s assets. An installer built specifically for `triple`
+/// is preferred over the generic installer for `os`.
+///
+/// If `installer_pattern` is set, it replaces `{app_name}-installer` as the
+/// installer's name: the generic installer is `{installer_pattern}.sh`, and
+/// a platform-specific one is any installer starting with the pattern whose
+/// name contains `triple`.
+pub(crate) fn find_installer<'a>(
+    release: &'a Release,
+    app_name: &str,
+    installer_pattern: Option<&str>,
+    os: &str,
+    triple: Option<&str>,
+) -> Option<&'a Asset> {
+    let extension = match os {
+        "macos" | "linux" => "sh",
+        "windows" => "ps1",
+        _ => return None,
+    };
+
+    let find = |name: String| release.find_asset(&name);
+
+    if let Some(pattern) = installer_pattern {
+        return triple
+            .and_then(|triple| find(format!("{pattern}*{triple}*.{extension}")))
+            .or_else(|| find(format!("{pattern}.{extension}")));
+    }
+
+    triple
+        .and_then(|triple| find(format!("{app_name}-{triple}-installer.{extension}")))
+        .or_else(|| find(format!("{app_name}-installer.{extension}")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_installer, native_triple_for};
+    use crate::test::fixtures::{asset, release_with_assets};
+
+    #[test]
+    fn test_find_installer_for_triple() {
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                asset("axolotlsay-aarch64-apple-darwin-installer.sh"),
+                asset("axolotlsay-x86_64-unknown-linux-gnu-installer.sh"),
+                asset("axolotlsay-x86_64-unknown-linux-musl-installer.sh"),
+                asset("axolotlsay-x86_64-pc-windows-msvc-installer.ps1"),
+                asset("axolotlsay-installer.sh"),
+            ],
+        );
+        let find = |os: &str, triple: Option<&str>| {
+            find_installer(&release, "axolotlsay", None, os, triple).map(|a| a.name.as_str())
+        };
+
+        assert_eq!(
+            find("linux", Some("x86_64-unknown-linux-musl")),
+            Some("axolotlsay-x86_64-unknown-linux-musl-installer.sh")
+        );
+        assert_eq!(
+            find("macos", Some("aarch64-apple-darwin")),
+            Some("axolotlsay-aarch64-apple-darwin-installer.sh")
+        );
+        assert_eq!(
+            find("windows", Some("x86_64-pc-windows-msvc")),
+            Some("axolotlsay-x86_64-pc-windows-msvc-installer.ps1")
+        );
+        // No installer for this triple; fall back to the generic one
+        assert_eq!(
+            find("macos", Some("x86_64-apple-darwin")),
+            Some("axolotlsay-installer.sh")
+        );
+        assert_eq!(find("linux", None), Some("axolotlsay-installer.sh"));
+        // ...unless there isn't one either
+        assert_eq!(find("windows", Some("aarch64-pc-windows-msvc")), None);
+        assert_eq!(find("freebsd", None), None);
+    }
+
+    #[test]
+    fn test_find_installer_with_pattern() {
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                asset("axolotlsay-setup-aarch64-apple-darwin.sh"),
+                asset("axolotlsay-setup.sh"),
+                asset("axolotlsay-setup.ps1"),
+                asset("axolotlsay-installer.sh"),
+            ],
+        );
+        let find = |os: &str, triple: Option<&str>| {
+            find_installer(&release, "axolotlsay", Some("axolotlsay-setup"), os, triple)
+                .map(|a| a.name.as_str())
+        };
+
+        assert_eq!(
+            find("macos", Some("aarch64-apple-darwin")),
+            Some("axolotlsay-setup-aarch64-apple-darwin.sh")
+        );
+        assert_eq!(
+            find("linux", Some("x86_64-unknown-linux-gnu")),
+            Some("axolotlsay-setup.sh")
+        );
+        assert_eq!(
+            find("windows", Some("x86_64-pc-windows-msvc")),
+            Some("axolotlsay-setup.ps1")
+        );
+    }
+
+    #[test]
+    fn test_native_triple_for() {
+        assert_eq!(
+            native_triple_for("macos", "x86_64", true).as_deref(),
+            Some("aarch64-apple-darwin")
+        );
+        assert_eq!(native_triple_for("macos", "x86_64", false), None);
+        assert_eq!(native_triple_for("macos", "aarch64", false), None);
+        assert_eq!(native_triple_for("linux", "x86_64", true), None);
+    }
+}
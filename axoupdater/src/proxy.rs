@@ -0,0 +1,164 @@
+//! Sending network requests through explicitly configured proxies
+
+use std::fmt;
+
+use axoasset::reqwest;
+
+use crate::{errors::*, AxoUpdater};
+
+/// Proxies to send network requests through, in place of any configured in
+/// the environment. Pass this to `AxoUpdater::set_proxy`.
+///
+/// Proxy URLs may use the `http` or `https` schemes. `socks5` and `socks5h`
+/// proxies are also supported when the `socks` feature is enabled.
+#[derive(Clone, Debug, Default)]
+pub struct ProxyConfig {
+    /// The proxy for plain HTTP requests, if any
+    pub http: Option<String>,
+    /// The proxy for HTTPS requests, if any
+    pub https: Option<String>,
+    /// Hosts to connect to directly instead of through a proxy, as a
+    /// comma-separated list in the same format as `NO_PROXY`
+    pub no_proxy: Option<String>,
+    /// The username and password to authenticate with the proxies, if any
+    pub credentials: Option<ProxyCredentials>,
+}
+
+/// A username and password for authenticating with a proxy
+#[derive(Clone)]
+pub struct ProxyCredentials {
+    /// The username
+    pub username: String,
+    /// The password
+    pub password: String,
+}
+
+impl fmt::Debug for ProxyCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ProxyConfig {
+    /// Configures `builder` to send requests through these proxies, and only
+    /// these proxies; any configured in the environment are ignored.
+    pub(crate) fn apply(
+        &self,
+        builder: reqwest::ClientBuilder,
+    ) -> AxoupdateResult<reqwest::ClientBuilder> {
+        let mut builder = builder.no_proxy();
+        let no_proxy = self
+            .no_proxy
+            .as_deref()
+            .and_then(reqwest::NoProxy::from_string);
+
+        let mut proxies = vec![];
+        if let Some(url) = &self.http {
+            proxies.push(reqwest::Proxy::http(url)?);
+        }
+        if let Some(url) = &self.https {
+            proxies.push(reqwest::Proxy::https(url)?);
+        }
+        for proxy in proxies {
+            let mut proxy = proxy.no_proxy(no_proxy.clone());
+            if let Some(credentials) = &self.credentials {
+                proxy = proxy.basic_auth(&credentials.username, &credentials.password);
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(builder)
+    }
+}
+
+impl AxoUpdater {
+    /// Configures the proxies to send network requests through, replacing
+    /// any configured in the environment with `HTTP_PROXY`, `HTTPS_PROXY`,
+    /// `ALL_PROXY` and `NO_PROXY`.
+    /// This applies to requests to GitHub and to asset downloads, and makes
+    /// their behaviour independent of the environment, which is useful in
+    /// tests. `set_no_proxy(true)` takes precedence over this.
+    ///
+    /// Requests to Axo Releases are made by gazenot's own HTTP client, which
+    /// can't be configured by axoupdater; it keeps using the environment's
+    /// proxy settings.
+    pub fn set_proxy(&mut self, proxy: ProxyConfig) -> &mut AxoUpdater {
+        self.proxy = Some(proxy);
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    use super::{ProxyConfig, ProxyCredentials};
+    use crate::AxoUpdater;
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_proxy() {
+        let proxy = MockServer::start_async().await;
+        // Requests sent through the proxy are logged by it, and name the
+        // host they were meant for
+        let proxied = proxy
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/releases")
+                    .header("host", "axoupdater.invalid")
+                    .header("proxy-authorization", "Basic dXNlcjpwYXNz");
+                then.status(200).body("proxied");
+            })
+            .await;
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_proxy(ProxyConfig {
+            http: Some(proxy.base_url()),
+            credentials: Some(ProxyCredentials {
+                username: "user".to_owned(),
+                password: "pass".to_owned(),
+            }),
+            ..ProxyConfig::default()
+        });
+        let response = updater
+            .http_client()
+            .unwrap()
+            .get("http://axoupdater.invalid/releases")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "proxied");
+        proxied.assert_hits_async(1).await;
+
+        // Hosts excluded from proxying are connected to directly
+        updater.set_proxy(ProxyConfig {
+            http: Some(proxy.base_url()),
+            no_proxy: Some("axoupdater.invalid".to_owned()),
+            ..ProxyConfig::default()
+        });
+        let direct = updater
+            .http_client()
+            .unwrap()
+            .get("http://axoupdater.invalid/releases")
+            .send()
+            .await;
+
+        assert!(direct.is_err());
+        proxied.assert_hits_async(1).await;
+    }
+
+    #[test]
+    fn test_proxy_credentials_redacted() {
+        let credentials = ProxyCredentials {
+            username: "user".to_owned(),
+            password: "hunter2".to_owned(),
+        };
+        assert!(!format!("{credentials:?}").contains("hunter2"));
+    }
+}
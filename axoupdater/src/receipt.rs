@@ -3,18 +3,20 @@ use std::{
     path::PathBuf,
 };
 
-use crate::{errors::*, AxoUpdater, ReleaseSource};
-use axoasset::SourceFile;
+use crate::{
+    errors::*, is_bin_dir, AxoUpdater, EligibilityResult, InstallationMethod, ReleaseSource,
+};
+use axoasset::{serde_json, LocalAsset, SourceFile};
 use axotag::Version;
-use camino::Utf8PathBuf;
-use serde::Deserialize;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
 
 fn default_as_true() -> bool {
     true
 }
 
 /// Information parsed from a cargo-dist install receipt
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InstallReceipt {
     /// The path this app has been installed to
     pub install_prefix: Utf8PathBuf,
@@ -28,16 +30,22 @@ pub struct InstallReceipt {
     pub source: ReleaseSource,
     /// Installed version
     pub version: String,
-    /// Information about the tool used to produce this receipt
-    pub provider: ReceiptProvider,
+    /// Information about the tool used to produce this receipt. This is
+    /// `None` if the receipt didn't match the expected format and its
+    /// provider couldn't be read.
+    #[serde(default)]
+    pub provider: Option<ReceiptProvider>,
     /// Information about whether new installations should modify system paths
     // Added in cargo-dist 0.23.0, missing in older receipts
     #[serde(default = "default_as_true")]
     pub modify_path: bool,
+    /// The version of the receipt format, if the receipt specifies one
+    #[serde(default)]
+    pub schema_version: Option<u32>,
 }
 
 /// Tool used to produce this install receipt
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ReceiptProvider {
     /// The name of the tool used to create this receipt
     pub source: String,
@@ -45,10 +53,88 @@ pub struct ReceiptProvider {
     pub version: String,
 }
 
+/// Storage for install receipts. By default, receipts are read from and
+/// written to the filesystem where cargo-dist's installers place them
+/// (see `FilesystemReceiptStore`); implement this to keep them elsewhere,
+/// such as a database or a sidecar file in an immutable image.
+pub trait ReceiptStore: Send + Sync {
+    /// Loads the install receipt for the app named `app_name`.
+    fn load(&self, app_name: &str) -> AxoupdateResult<InstallReceipt>;
+
+    /// Saves `receipt` as the install receipt for the app named `app_name`.
+    fn save(&self, app_name: &str, receipt: &InstallReceipt) -> AxoupdateResult<()>;
+}
+
+/// The default `ReceiptStore`, which keeps receipts at
+/// `{config}/{app_name}/{app_name}-receipt.json` as cargo-dist's installers
+/// do.
+#[derive(Clone, Debug, Default)]
+pub struct FilesystemReceiptStore {
+    /// The directory under the platform's config directory to keep
+    /// receipts in, if not the app's name
+    pub config_subdir: Option<Utf8PathBuf>,
+}
+
+impl ReceiptStore for FilesystemReceiptStore {
+    fn load(&self, app_name: &str) -> AxoupdateResult<InstallReceipt> {
+        load_receipt_for(app_name, self.config_subdir.as_deref())
+    }
+
+    fn save(&self, app_name: &str, receipt: &InstallReceipt) -> AxoupdateResult<()> {
+        let subdir = self.config_subdir.as_deref();
+        // Overwrite the existing receipt if there is one; otherwise, write to
+        // the preferred config directory.
+        let path = match get_receipt_path(app_name, subdir)? {
+            Some(path) => path,
+            None => get_config_paths(app_name, subdir)?
+                .remove(0)
+                .join(format!("{app_name}-receipt.json")),
+        };
+
+        let contents =
+            serde_json::to_string(receipt).map_err(|e| AxoupdateError::ReceiptSaveFailed {
+                app_name: app_name.to_owned(),
+                message: e.to_string(),
+            })?;
+        if let Some(parent) = path.parent() {
+            LocalAsset::create_dir_all(parent)?;
+        }
+        LocalAsset::write_new(&contents, &path)?;
+
+        Ok(())
+    }
+}
+
 impl AxoUpdater {
+    /// Configures AxoUpdater to load install receipts from `store` instead of
+    /// the filesystem. This takes precedence over `set_config_subdir`,
+    /// whichever is called first.
+    pub fn set_receipt_store(&mut self, store: Box<dyn ReceiptStore>) -> &mut AxoUpdater {
+        self.receipt_store = Some(store);
+
+        self
+    }
+
+    /// Configures the directory, under the platform's config directory, that
+    /// install receipts are kept in. By default this is the app's name, so
+    /// receipts are found at `{config}/{app_name}/{app_name}-receipt.json`;
+    /// apps which keep them in a vendor-specific directory can set this to,
+    /// for example, `MyCompany/my-app`.
+    ///
+    /// The `AXOUPDATER_CONFIG_PATH` environment variable takes precedence
+    /// over this. If a receipt store was configured with `set_receipt_store`,
+    /// receipts are loaded from it instead, and this only affects where other
+    /// metadata, like the release cache, is kept.
+    pub fn set_config_subdir(&mut self, subdir: Utf8PathBuf) -> &mut AxoUpdater {
+        self.config_subdir = Some(subdir);
+
+        self
+    }
+
     /// Attempts to load an install receipt in order to prepare for an update.
     /// If present and valid, the install receipt is used to populate the
-    /// `source` and `current_version` fields.
+    /// `source` and `current_version` fields. A source set with
+    /// `set_release_source` isn't replaced by the receipt's.
     /// Shell and Powershell installers produced by cargo-dist since 0.9.0
     /// will have created an install receipt.
     pub fn load_receipt(&mut self) -> AxoupdateResult<&mut AxoUpdater> {
@@ -64,18 +150,49 @@ impl AxoUpdater {
     /// useful if the receipt may exist under several different names, for
     /// example if an app has been renamed.
     pub fn load_receipt_as(&mut self, app_name: &str) -> AxoupdateResult<&mut AxoUpdater> {
-        let receipt = load_receipt_for(app_name)?;
+        let receipt = self.load_stored_receipt(app_name)?;
+
+        self.apply_receipt(receipt)
+    }
+
+    /// Loads the install receipt for the app named `app_name` from the
+    /// configured receipt store, or from the filesystem under the config
+    /// subdirectory if there isn't one.
+    fn load_stored_receipt(&self, app_name: &str) -> AxoupdateResult<InstallReceipt> {
+        match &self.receipt_store {
+            Some(store) => store.load(app_name),
+            None => load_receipt_for(app_name, self.config_subdir.as_deref()),
+        }
+    }
 
-        self.source = Some(receipt.source);
+    /// Similar to `AxoUpdater::load_receipt`, but loads the receipt at
+    /// `path` instead of searching the usual config directories for it.
+    /// This is useful when the caller already knows exactly where its
+    /// receipt was written.
+    pub fn load_receipt_from(&mut self, path: Utf8PathBuf) -> AxoupdateResult<&mut AxoUpdater> {
+        let app_name = self.name.clone().unwrap_or_default();
+        let receipt = load_receipt_from_path(&app_name, &path)?;
+
+        self.apply_receipt(receipt)
+    }
+
+    /// Fills in the information about the installed copy of the app from a
+    /// parsed receipt.
+    fn apply_receipt(&mut self, receipt: InstallReceipt) -> AxoupdateResult<&mut AxoUpdater> {
+        if !self.source_overridden {
+            self.source = Some(receipt.source);
+        }
         self.current_version = Some(receipt.version.parse::<Version>()?);
 
-        let provider = crate::Provider {
-            source: receipt.provider.source,
-            version: receipt.provider.version.parse::<Version>()?,
+        self.current_version_installed_by = match receipt.provider {
+            Some(provider) => Some(crate::Provider {
+                source: provider.source,
+                version: provider.version.parse::<Version>()?,
+            }),
+            None => None,
         };
-
-        self.current_version_installed_by = Some(provider);
         self.install_prefix = Some(receipt.install_prefix);
+        self.binaries = receipt.binaries;
         self.modify_path = receipt.modify_path;
 
         Ok(self)
@@ -87,8 +204,17 @@ impl AxoUpdater {
     /// system.
     /// Returns an error if the receipt hasn't been loaded yet.
     pub fn check_receipt_is_for_this_executable(&self) -> AxoupdateResult<bool> {
+        Ok(self.check_eligibility()? == EligibilityResult::Eligible)
+    }
+
+    /// Like `check_receipt_is_for_this_executable`, but returns information
+    /// about why the executable isn't eligible for updates if the receipt
+    /// doesn't match it.
+    /// Returns an error if the receipt hasn't been loaded yet.
+    pub fn check_eligibility(&self) -> AxoupdateResult<EligibilityResult> {
         let current_exe_path = Utf8PathBuf::from_path_buf(current_exe()?.canonicalize()?)
             .map_err(|path| AxoupdateError::CaminoConversionFailed { path })?;
+        let exe_name = current_exe_path.file_name().unwrap_or_default().to_owned();
         // First determine the parent dir
         let mut current_exe_root = if let Some(parent) = current_exe_path.parent() {
             parent.to_path_buf()
@@ -100,7 +226,7 @@ impl AxoUpdater {
 
         // If the parent dir is a "bin" dir, strip it to get the true root,
         // but only if the true install root isn't itself a `bin` dir.
-        if current_exe_root.file_name() == Some("bin") && receipt_root.file_name() != Some("bin") {
+        if is_bin_dir(current_exe_root.file_name()) && !is_bin_dir(receipt_root.file_name()) {
             if let Some(parent) = current_exe_root.parent() {
                 current_exe_root = parent.to_path_buf();
             }
@@ -109,27 +235,103 @@ impl AxoUpdater {
         // Looks like this EXE comes from a different source than the install
         // receipt
         if current_exe_root != receipt_root {
-            return Ok(false);
+            return Ok(EligibilityResult::NotManaged {
+                receipt_root,
+                exe_root: current_exe_root,
+            });
         }
 
-        Ok(true)
+        // The receipt's prefix may be shared by several apps, so also make
+        // sure that this is one of the binaries the receipt installed.
+        let app_name = self.name.clone().unwrap_or_default();
+        if !binary_is_in_receipt(&exe_name, &app_name, &self.binaries) {
+            return Ok(EligibilityResult::UnknownBinary {
+                binary: exe_name,
+                binaries: self.binaries.clone(),
+            });
+        }
+
+        Ok(EligibilityResult::Eligible)
+    }
+
+    /// Determines whether the running executable was installed by one of
+    /// cargo-dist's installers, and can therefore be updated. This is useful
+    /// for hiding an app's "update" option when it's managed by a package
+    /// manager such as apt or Homebrew instead.
+    ///
+    /// If no install receipt has been loaded yet, this looks for one without
+    /// loading it into this updater. Having no receipt at all, or one for a
+    /// copy installed elsewhere, is reported as `InstallationMethod::Unmanaged`.
+    pub fn installation_method(&self) -> InstallationMethod {
+        if self.install_prefix.is_some() {
+            return installation_method_of(self.check_eligibility());
+        }
+
+        let Some(app_name) = &self.name else {
+            return InstallationMethod::Unknown;
+        };
+        match self.load_stored_receipt(app_name) {
+            Ok(receipt) => {
+                let mut probe = AxoUpdater::new_for(app_name);
+                match probe.apply_receipt(receipt) {
+                    Ok(probe) => installation_method_of(probe.check_eligibility()),
+                    Err(_) => InstallationMethod::Unknown,
+                }
+            }
+            Err(AxoupdateError::NoReceipt { .. }) => InstallationMethod::Unmanaged,
+            Err(_) => InstallationMethod::Unknown,
+        }
     }
 }
 
+fn installation_method_of(eligibility: AxoupdateResult<EligibilityResult>) -> InstallationMethod {
+    match eligibility {
+        Ok(EligibilityResult::Eligible) => InstallationMethod::AxoInstaller,
+        Ok(_) => InstallationMethod::Unmanaged,
+        Err(_) => InstallationMethod::Unknown,
+    }
+}
+
+/// Checks whether `exe_name` is one of the binaries listed in an install
+/// receipt. The standalone updater, `{app_name}-update`, is installed
+/// alongside those binaries and is also accepted. If the receipt didn't
+/// list any binaries, any name is accepted.
+fn binary_is_in_receipt(exe_name: &str, app_name: &str, binaries: &[String]) -> bool {
+    if binaries.is_empty() {
+        return true;
+    }
+
+    let exe_name = exe_name.strip_suffix(".exe").unwrap_or(exe_name);
+    if exe_name == format!("{app_name}-update") {
+        return true;
+    }
+
+    binaries
+        .iter()
+        .any(|binary| binary.strip_suffix(".exe").unwrap_or(binary) == exe_name)
+}
+
 /// Returns a Vec of possible receipt locations, beginning with
-/// `XDG_CONFIG_HOME` (if set).
-pub(crate) fn get_config_paths(app_name: &str) -> AxoupdateResult<Vec<Utf8PathBuf>> {
+/// `XDG_CONFIG_HOME` (if set). Receipts are kept in the directory `subdir`
+/// under each config directory, or in one named after the app if `None`.
+pub(crate) fn get_config_paths(
+    app_name: &str,
+    subdir: Option<&Utf8Path>,
+) -> AxoupdateResult<Vec<Utf8PathBuf>> {
     let mut potential_homes = vec![];
+    let subdir = subdir.unwrap_or(Utf8Path::new(app_name));
 
     if env::var("AXOUPDATER_CONFIG_WORKING_DIR").is_ok() {
         Ok(vec![Utf8PathBuf::try_from(current_dir()?)?])
     } else if let Ok(path) = env::var("AXOUPDATER_CONFIG_PATH") {
         Ok(vec![Utf8PathBuf::from(path)])
     } else {
+        // An empty XDG_CONFIG_HOME is treated as unset, per the XDG spec
         let xdg_home = env::var("XDG_CONFIG_HOME")
             .ok()
+            .filter(|home| !home.is_empty())
             .map(Utf8PathBuf::from)
-            .map(|h| h.join(app_name));
+            .map(|h| h.join(subdir));
         if let Some(home) = &xdg_home {
             if home.exists() {
                 potential_homes.push(home.to_owned());
@@ -138,10 +340,10 @@ pub(crate) fn get_config_paths(app_name: &str) -> AxoupdateResult<Vec<Utf8PathBu
         let home = if cfg!(windows) {
             env::var("LOCALAPPDATA")
                 .map(PathBuf::from)
-                .map(|h| h.join(app_name))
+                .map(|h| h.join(subdir))
                 .ok()
         } else {
-            homedir::my_home()?.map(|path| path.join(".config").join(app_name))
+            homedir::my_home()?.map(|path| path.join(".config").join(subdir))
         };
         if let Some(home) = home {
             potential_homes.push(Utf8PathBuf::try_from(home)?);
@@ -157,8 +359,11 @@ pub(crate) fn get_config_paths(app_name: &str) -> AxoupdateResult<Vec<Utf8PathBu
 
 /// Iterates through the list of possible receipt locations from
 /// `get_config_paths` and returns the first that contains a valid receipt.
-pub(crate) fn get_receipt_path(app_name: &str) -> AxoupdateResult<Option<Utf8PathBuf>> {
-    for receipt_prefix in get_config_paths(app_name)? {
+pub(crate) fn get_receipt_path(
+    app_name: &str,
+    subdir: Option<&Utf8Path>,
+) -> AxoupdateResult<Option<Utf8PathBuf>> {
+    for receipt_prefix in get_config_paths(app_name, subdir)? {
         let install_receipt_path = receipt_prefix.join(format!("{app_name}-receipt.json"));
         if install_receipt_path.exists() {
             return Ok(Some(install_receipt_path));
@@ -168,18 +373,427 @@ pub(crate) fn get_receipt_path(app_name: &str) -> AxoupdateResult<Option<Utf8Pat
     Ok(None)
 }
 
-fn load_receipt_from_path(install_receipt_path: &Utf8PathBuf) -> AxoupdateResult<InstallReceipt> {
-    Ok(SourceFile::load_local(install_receipt_path)?.deserialize_json()?)
+/// Loads the receipt at `install_receipt_path`. Receipts which aren't valid
+/// JSON at all are reported as `ReceiptCorrupt`, with the location of the
+/// problem, rather than as a generic parse failure.
+fn load_receipt_from_path(
+    app_name: &str,
+    install_receipt_path: &Utf8PathBuf,
+) -> AxoupdateResult<InstallReceipt> {
+    let source = SourceFile::load_local(install_receipt_path)?;
+    if let Err(e) = serde_json::from_str::<serde_json::Value>(source.contents()) {
+        return Err(AxoupdateError::ReceiptCorrupt {
+            app_name: app_name.to_owned(),
+            path: install_receipt_path.to_owned(),
+            detail: e.to_string(),
+        });
+    }
+
+    parse_receipt(&source)
+}
+
+/// Parses an install receipt. If the receipt doesn't match the format we
+/// expect, for example because it was written by a newer version of
+/// cargo-dist, falls back to `parse_receipt_leniently`.
+fn parse_receipt(source: &SourceFile) -> AxoupdateResult<InstallReceipt> {
+    match source.deserialize_json::<InstallReceipt>() {
+        Ok(receipt) => Ok(receipt),
+        Err(e) => parse_receipt_leniently(source).ok_or(e.into()),
+    }
+}
+
+/// Recovers the fields axoupdater needs in order to perform an update from a
+/// receipt which couldn't be parsed in full. All other fields are replaced
+/// with their defaults. Returns `None` if any of the required fields are
+/// missing or invalid.
+fn parse_receipt_leniently(source: &SourceFile) -> Option<InstallReceipt> {
+    let value = source.deserialize_json::<serde_json::Value>().ok()?;
+    let mut missing = vec![];
+
+    fn field<T: serde::de::DeserializeOwned>(
+        value: &serde_json::Value,
+        name: &'static str,
+        missing: &mut Vec<&'static str>,
+    ) -> Option<T> {
+        let parsed = value
+            .get(name)
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        if parsed.is_none() {
+            missing.push(name);
+        }
+        parsed
+    }
+
+    let install_prefix = field(&value, "install_prefix", &mut missing);
+    let source = field(&value, "source", &mut missing);
+    let version = field(&value, "version", &mut missing);
+    let binaries = field(&value, "binaries", &mut missing);
+    let cdylibs = field(&value, "cdylibs", &mut missing);
+    let provider = field(&value, "provider", &mut missing);
+    let modify_path = field(&value, "modify_path", &mut missing);
+    let schema_version = field(&value, "schema_version", &mut missing);
+
+    let receipt = InstallReceipt {
+        install_prefix: install_prefix?,
+        source: source?,
+        version: version?,
+        binaries: binaries.unwrap_or_default(),
+        cdylibs: cdylibs.unwrap_or_default(),
+        provider,
+        modify_path: modify_path.unwrap_or(true),
+        schema_version,
+    };
+
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        ?missing,
+        "install receipt didn't match the expected format; missing or invalid fields were ignored"
+    );
+
+    Some(receipt)
+}
+
+/// Reads the install receipt for the app named `app_name`, without
+/// preparing an update. This can be used to inspect the installed version,
+/// binaries and install prefix of an app installed by cargo-dist.
+pub fn read_receipt(app_name: &str) -> AxoupdateResult<InstallReceipt> {
+    load_receipt_for(app_name, None)
 }
 
-fn load_receipt_for(app_name: &str) -> AxoupdateResult<InstallReceipt> {
-    let Some(install_receipt_path) = get_receipt_path(app_name)? else {
+fn load_receipt_for(app_name: &str, subdir: Option<&Utf8Path>) -> AxoupdateResult<InstallReceipt> {
+    let Some(install_receipt_path) = get_receipt_path(app_name, subdir)? else {
         return Err(AxoupdateError::NoReceipt {
             app_name: app_name.to_owned(),
         });
     };
 
-    load_receipt_from_path(&install_receipt_path).map_err(|_| AxoupdateError::ReceiptLoadFailed {
-        app_name: app_name.to_owned(),
+    load_receipt_from_path(app_name, &install_receipt_path).map_err(|e| match e {
+        AxoupdateError::ReceiptCorrupt { .. } => e,
+        _ => AxoupdateError::ReceiptLoadFailed {
+            app_name: app_name.to_owned(),
+        },
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        binary_is_in_receipt, get_config_paths, parse_receipt, read_receipt,
+        FilesystemReceiptStore, InstallReceipt, ReceiptStore,
+    };
+    use crate::{
+        AxoUpdater, AxoupdateError, AxoupdateResult, InstallationMethod, ReleaseSource,
+        ReleaseSourceType,
+    };
+    use axoasset::SourceFile;
+    use camino::{Utf8Path, Utf8PathBuf};
+    use serial_test::serial;
+    use std::{collections::HashMap, sync::Mutex};
+
+    const RECEIPT: &str = r#"{"binaries":["axolotlsay"],"install_prefix":"/home/axo/.cargo","provider":{"source":"cargo-dist","version":"0.30.0"},"source":{"app_name":"axolotlsay","name":"cargodisttest","owner":"mistydemeo","release_type":"github"},"version":"0.2.115"}"#;
+
+    #[derive(Default)]
+    struct MemoryReceiptStore(Mutex<HashMap<String, InstallReceipt>>);
+
+    impl ReceiptStore for MemoryReceiptStore {
+        fn load(&self, app_name: &str) -> AxoupdateResult<InstallReceipt> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(app_name)
+                .cloned()
+                .ok_or(crate::AxoupdateError::NoReceipt {
+                    app_name: app_name.to_owned(),
+                })
+        }
+
+        fn save(&self, app_name: &str, receipt: &InstallReceipt) -> AxoupdateResult<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(app_name.to_owned(), receipt.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_custom_receipt_store() {
+        let receipt = parse_receipt(&SourceFile::new("receipt.json", RECEIPT.to_owned())).unwrap();
+        let store = MemoryReceiptStore::default();
+        store.save("axolotlsay", &receipt).unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_receipt_store(Box::new(store));
+        updater.load_receipt().unwrap();
+        assert_eq!(
+            updater.install_prefix.as_ref().unwrap().as_str(),
+            "/home/axo/.cargo"
+        );
+        assert_eq!(updater.binaries, vec!["axolotlsay".to_owned()]);
+
+        let mut updater = AxoUpdater::new_for("otherapp");
+        updater.set_receipt_store(Box::new(MemoryReceiptStore::default()));
+        assert!(updater.load_receipt().is_err());
+
+        // Setting a config subdirectory afterwards doesn't replace the store
+        let store = MemoryReceiptStore::default();
+        store.save("axolotlsay", &receipt).unwrap();
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater
+            .set_receipt_store(Box::new(store))
+            .set_config_subdir(Utf8PathBuf::from("MyCompany/axolotlsay"));
+        updater.load_receipt().unwrap();
+        assert_eq!(updater.binaries, vec!["axolotlsay".to_owned()]);
+    }
+
+    #[test]
+    fn test_installation_method() {
+        let receipt = parse_receipt(&SourceFile::new("receipt.json", RECEIPT.to_owned())).unwrap();
+        let exe_dir = std::env::current_exe()
+            .unwrap()
+            .canonicalize()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_owned();
+        let ours = InstallReceipt {
+            install_prefix: Utf8PathBuf::try_from(exe_dir).unwrap(),
+            binaries: vec![],
+            ..receipt.clone()
+        };
+        let store = MemoryReceiptStore::default();
+        store.save("axolotlsay", &receipt).unwrap();
+        store.save("ours", &ours).unwrap();
+        let updater = |app_name: &str| {
+            let mut updater = AxoUpdater::new_for(app_name);
+            updater.set_receipt_store(Box::new(MemoryReceiptStore(Mutex::new(
+                store.0.lock().unwrap().clone(),
+            ))));
+            updater
+        };
+
+        // A receipt for a copy installed somewhere else
+        assert_eq!(
+            updater("axolotlsay").installation_method(),
+            InstallationMethod::Unmanaged
+        );
+        // No receipt at all
+        assert_eq!(
+            updater("otherapp").installation_method(),
+            InstallationMethod::Unmanaged
+        );
+        assert_eq!(
+            updater("ours").installation_method(),
+            InstallationMethod::AxoInstaller
+        );
+
+        // An already-loaded receipt is used as-is
+        let mut loaded = updater("ours");
+        loaded.load_receipt().unwrap();
+        assert_eq!(
+            loaded.installation_method(),
+            InstallationMethod::AxoInstaller
+        );
+
+        assert_eq!(
+            AxoUpdater::new().installation_method(),
+            InstallationMethod::Unknown
+        );
+    }
+
+    #[test]
+    fn test_load_receipt_from() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let path = Utf8PathBuf::try_from(tempdir.path().join("receipt.json")).unwrap();
+        std::fs::write(&path, RECEIPT).unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.load_receipt_from(path).unwrap();
+        assert_eq!(updater.source.as_ref().unwrap().owner, "mistydemeo");
+        assert_eq!(
+            updater.current_version.as_ref().unwrap().to_string(),
+            "0.2.115"
+        );
+        assert_eq!(
+            updater.install_prefix.as_ref().unwrap().as_str(),
+            "/home/axo/.cargo"
+        );
+
+        let missing = Utf8PathBuf::try_from(tempdir.path().join("missing.json")).unwrap();
+        assert!(updater.load_receipt_from(missing).is_err());
+    }
+
+    #[test]
+    #[serial] // modifying the global state environment variables
+    fn test_filesystem_receipt_store_round_trip() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let receipt = parse_receipt(&SourceFile::new("receipt.json", RECEIPT.to_owned())).unwrap();
+
+        std::env::set_var("AXOUPDATER_CONFIG_PATH", tempdir.path());
+        let saved = FilesystemReceiptStore::default().save("axolotlsay", &receipt);
+        let loaded = FilesystemReceiptStore::default().load("axolotlsay");
+        std::env::remove_var("AXOUPDATER_CONFIG_PATH");
+
+        saved.unwrap();
+        assert!(tempdir.path().join("axolotlsay-receipt.json").exists());
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.version, "0.2.115");
+        assert_eq!(loaded.source.app_name, "axolotlsay");
+        assert_eq!(loaded.provider.unwrap().version, "0.30.0");
+    }
+
+    #[test]
+    fn test_binary_is_in_receipt() {
+        let binaries = vec!["foo".to_owned(), "bar".to_owned()];
+
+        assert!(binary_is_in_receipt("foo", "foo", &binaries));
+        assert!(binary_is_in_receipt("bar.exe", "foo", &binaries));
+        assert!(binary_is_in_receipt("foo-update", "foo", &binaries));
+        assert!(!binary_is_in_receipt("baz", "foo", &binaries));
+        assert!(!binary_is_in_receipt("baz-update", "foo", &binaries));
+        // Receipts without a binary list can't rule anything out
+        assert!(binary_is_in_receipt("baz", "foo", &[]));
+    }
+
+    #[test]
+    fn test_parse_receipt_with_unknown_fields() {
+        let contents = r#"{"binaries":["axolotlsay"],"install_prefix":"/home/axo/.cargo","provider":{"source":"cargo-dist","version":"0.30.0"},"source":{"app_name":"axolotlsay","name":"cargodisttest","owner":"mistydemeo","release_type":"github"},"version":"0.2.115","schema_version":2,"platform_info":{"arch":"x86_64"}}"#;
+        let receipt = parse_receipt(&SourceFile::new("receipt.json", contents.to_owned())).unwrap();
+
+        assert_eq!(receipt.version, "0.2.115");
+        assert_eq!(receipt.schema_version, Some(2));
+        assert_eq!(receipt.binaries, vec!["axolotlsay".to_owned()]);
+        assert!(receipt.provider.is_some());
+    }
+
+    #[test]
+    fn test_parse_receipt_leniently() {
+        // A future receipt where a field we don't need has changed shape
+        let contents = r#"{"binaries":["axolotlsay"],"install_prefix":"/home/axo/.cargo","provider":"cargo-dist 1.0.0","modify_path":"sometimes","source":{"app_name":"axolotlsay","name":"cargodisttest","owner":"mistydemeo","release_type":"github"},"version":"0.2.115"}"#;
+        let receipt = parse_receipt(&SourceFile::new("receipt.json", contents.to_owned())).unwrap();
+
+        assert_eq!(receipt.version, "0.2.115");
+        assert_eq!(receipt.install_prefix.as_str(), "/home/axo/.cargo");
+        assert_eq!(receipt.source.app_name, "axolotlsay");
+        assert!(receipt.provider.is_none());
+        assert!(receipt.modify_path);
+
+        // ...but fields we need still have to be present
+        let contents = r#"{"binaries":["axolotlsay"],"provider":"cargo-dist 1.0.0","source":{"app_name":"axolotlsay","name":"cargodisttest","owner":"mistydemeo","release_type":"github"},"version":"0.2.115"}"#;
+        assert!(parse_receipt(&SourceFile::new("receipt.json", contents.to_owned())).is_err());
+    }
+
+    #[test]
+    #[serial] // modifying the global state environment variables
+    fn test_read_receipt() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let contents = r#"{"binaries":["axolotlsay"],"install_prefix":"/home/axo/.cargo","provider":{"source":"cargo-dist","version":"0.30.0"},"source":{"app_name":"axolotlsay","name":"cargodisttest","owner":"mistydemeo","release_type":"github"},"version":"0.2.115"}"#;
+        std::fs::write(tempdir.path().join("axolotlsay-receipt.json"), contents).unwrap();
+
+        std::env::set_var("AXOUPDATER_CONFIG_PATH", tempdir.path());
+        let receipt = read_receipt("axolotlsay");
+        let missing = read_receipt("otherapp");
+        std::env::remove_var("AXOUPDATER_CONFIG_PATH");
+
+        let receipt = receipt.unwrap();
+        assert_eq!(receipt.version, "0.2.115");
+        assert_eq!(receipt.binaries, vec!["axolotlsay".to_owned()]);
+        assert_eq!(receipt.install_prefix.as_str(), "/home/axo/.cargo");
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    #[serial] // modifying the global state environment variables
+    fn test_config_paths_xdg() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let xdg_home = Utf8PathBuf::from_path_buf(tempdir.path().to_path_buf()).unwrap();
+        std::fs::create_dir(xdg_home.join("axolotlsay")).unwrap();
+
+        std::env::set_var("XDG_CONFIG_HOME", &xdg_home);
+        let with_xdg = get_config_paths("axolotlsay", None).unwrap();
+        // Only used if the app's directory exists inside it
+        let other_app = get_config_paths("otherapp", None).unwrap();
+        std::env::set_var("AXOUPDATER_CONFIG_PATH", "/custom/path");
+        let overridden = get_config_paths("axolotlsay", None).unwrap();
+        std::env::remove_var("AXOUPDATER_CONFIG_PATH");
+        std::env::set_var("XDG_CONFIG_HOME", "");
+        let empty_xdg = get_config_paths("axolotlsay", None).unwrap();
+        std::env::remove_var("XDG_CONFIG_HOME");
+        let without_xdg = get_config_paths("axolotlsay", None).unwrap();
+
+        assert_eq!(with_xdg[0], xdg_home.join("axolotlsay"));
+        assert_eq!(with_xdg.len(), 2);
+        assert!(!other_app.contains(&xdg_home.join("otherapp")));
+        assert_eq!(overridden, vec![Utf8PathBuf::from("/custom/path")]);
+        assert_eq!(empty_xdg.len(), 1);
+        assert_eq!(without_xdg, empty_xdg);
+    }
+
+    #[test]
+    fn test_release_source_override() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let path = Utf8PathBuf::try_from(tempdir.path().join("receipt.json")).unwrap();
+        std::fs::write(&path, RECEIPT).unwrap();
+        let staging = ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "mistydemeo".to_owned(),
+            name: "cargodisttest-staging".to_owned(),
+            app_name: "axolotlsay".to_owned(),
+        };
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.load_receipt_from(path.clone()).unwrap();
+        assert_eq!(updater.source.as_ref().unwrap().name, "cargodisttest");
+        updater.set_release_source(staging.clone());
+        assert_eq!(
+            updater.source.as_ref().unwrap().name,
+            "cargodisttest-staging"
+        );
+
+        // Setting the source first isn't undone by loading the receipt
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_release_source(staging.clone());
+        updater.load_receipt_from(path).unwrap();
+        assert_eq!(
+            updater.source.as_ref().unwrap().name,
+            "cargodisttest-staging"
+        );
+        assert_eq!(updater.current_version().unwrap().to_string(), "0.2.115");
+    }
+
+    #[test]
+    fn test_corrupt_receipt() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let path = Utf8PathBuf::try_from(tempdir.path().join("receipt.json")).unwrap();
+        // Truncated partway through writing
+        std::fs::write(&path, &RECEIPT[..40]).unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        let Err(AxoupdateError::ReceiptCorrupt { detail, .. }) = updater.load_receipt_from(path)
+        else {
+            panic!("expected ReceiptCorrupt");
+        };
+        assert!(detail.contains("line 1"));
+    }
+
+    #[test]
+    #[serial] // modifying the global state environment variables
+    fn test_config_subdir() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let xdg_home = Utf8PathBuf::from_path_buf(tempdir.path().to_path_buf()).unwrap();
+        let vendor_dir = xdg_home.join("MyCompany").join("axolotlsay");
+        std::fs::create_dir_all(&vendor_dir).unwrap();
+        std::fs::write(vendor_dir.join("axolotlsay-receipt.json"), RECEIPT).unwrap();
+
+        std::env::set_var("XDG_CONFIG_HOME", &xdg_home);
+        let paths = get_config_paths("axolotlsay", Some(Utf8Path::new("MyCompany/axolotlsay")));
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.set_config_subdir(Utf8PathBuf::from("MyCompany/axolotlsay"));
+        let loaded = updater.load_receipt().map(|_| ());
+        std::env::remove_var("XDG_CONFIG_HOME");
+
+        assert_eq!(paths.unwrap()[0], vendor_dir);
+        loaded.unwrap();
+        assert_eq!(updater.current_version.unwrap().to_string(), "0.2.115");
+    }
+}
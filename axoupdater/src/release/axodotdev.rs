@@ -1,17 +1,76 @@
 //! Fetching and processing from axo Releases
 
-use super::{Asset, Release};
+use super::{Asset, Release, ReleaseBackend, SkippedTags};
 use crate::errors::*;
+use axoasset::reqwest;
 use axotag::Version;
+use futures_util::future::BoxFuture;
 use gazenot::Gazenot;
+use std::collections::HashMap;
+
+/// The built-in backend for releases hosted on Axo Releases
+pub(crate) struct AxoBackend {
+    pub(crate) name: String,
+    pub(crate) owner: String,
+    pub(crate) app_name: String,
+    pub(crate) token: Option<String>,
+    pub(crate) skipped_tags: SkippedTags,
+}
+
+impl ReleaseBackend for AxoBackend {
+    fn list(&self) -> BoxFuture<'_, AxoupdateResult<Vec<Release>>> {
+        Box::pin(get_axo_releases(
+            &self.name,
+            &self.owner,
+            &self.app_name,
+            &self.token,
+            &self.skipped_tags,
+        ))
+    }
+
+    fn specific_tag<'a>(&'a self, tag: &'a str) -> BoxFuture<'a, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move {
+            get_specific_axo_tag(
+                &self.name,
+                &self.owner,
+                &self.app_name,
+                tag,
+                &self.token,
+                &self.skipped_tags,
+            )
+            .await
+            .map(Some)
+        })
+    }
+
+    fn specific_version<'a>(
+        &'a self,
+        version: &'a Version,
+    ) -> BoxFuture<'a, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move {
+            get_specific_axo_version(
+                &self.name,
+                &self.owner,
+                &self.app_name,
+                version,
+                &self.token,
+                &self.skipped_tags,
+            )
+            .await
+            .map(Some)
+        })
+    }
+}
 
 pub(crate) async fn get_specific_axo_version(
     name: &str,
     owner: &str,
     app_name: &str,
     version: &Version,
+    token: &Option<String>,
+    skipped_tags: &SkippedTags,
 ) -> AxoupdateResult<Release> {
-    let releases = get_axo_releases(name, owner, app_name).await?;
+    let releases = get_axo_releases(name, owner, app_name, token, skipped_tags).await?;
     let release = releases.into_iter().find(|r| &r.version == version);
 
     if let Some(release) = release {
@@ -29,8 +88,10 @@ pub(crate) async fn get_specific_axo_tag(
     owner: &str,
     app_name: &str,
     tag: &str,
+    token: &Option<String>,
+    skipped_tags: &SkippedTags,
 ) -> AxoupdateResult<Release> {
-    let releases = get_axo_releases(name, owner, app_name).await?;
+    let releases = get_axo_releases(name, owner, app_name, token, skipped_tags).await?;
     let release = releases.into_iter().find(|r| r.tag_name == tag);
 
     if let Some(release) = release {
@@ -47,28 +108,120 @@ pub(crate) async fn get_axo_releases(
     name: &str,
     owner: &str,
     app_name: &str,
+    token: &Option<String>,
+    skipped_tags: &SkippedTags,
 ) -> AxoupdateResult<Vec<Release>> {
-    let abyss = Gazenot::new_unauthed("github".to_string(), owner)?;
-    let release_lists = abyss.list_releases_many(vec![app_name.to_owned()]).await?;
-    let Some(our_release) = release_lists
-        .into_iter()
-        .find(|rl| rl.package_name == app_name)
-    else {
+    let release_lists =
+        get_axo_releases_many(owner, &[app_name.to_owned()], token, skipped_tags).await?;
+
+    releases_for_app(name, app_name, release_lists)
+}
+
+/// Picks `app_name`'s releases out of the release lists fetched for its
+/// workspace. An app which isn't in the workspace at all is reported as
+/// `ReleaseNotFound`, and one without any releases as `NoStableReleases`.
+fn releases_for_app(
+    name: &str,
+    app_name: &str,
+    mut release_lists: HashMap<String, Vec<Release>>,
+) -> AxoupdateResult<Vec<Release>> {
+    let Some(releases) = release_lists.remove(app_name) else {
         return Err(AxoupdateError::ReleaseNotFound {
             name: name.to_owned(),
             app_name: app_name.to_owned(),
         });
     };
+    if releases.is_empty() {
+        return Err(AxoupdateError::NoStableReleases {
+            app_name: app_name.to_owned(),
+            status: None,
+            message: None,
+        });
+    }
+
+    Ok(releases)
+}
+
+/// Fetches the releases of several apps owned by `owner` in a single
+/// request. Apps which Axo Releases doesn't know about are left out of the
+/// result. If Axo Releases can't be reached at all, this fails with
+/// `AxoupdateError::NetworkError`.
+pub(crate) async fn get_axo_releases_many(
+    owner: &str,
+    app_names: &[String],
+    token: &Option<String>,
+    skipped_tags: &SkippedTags,
+) -> AxoupdateResult<HashMap<String, Vec<Release>>> {
+    // Private releases require a token; public ones can be read without one
+    let abyss = if let Some(token) = token {
+        Gazenot::new("github".to_string(), owner, token.to_owned())?
+    } else {
+        Gazenot::new_unauthed("github".to_string(), owner)?
+    };
+    let release_lists = abyss
+        .list_releases_many(app_names.to_vec())
+        .await
+        .map_err(|e| {
+            if is_connection_failure(&e) {
+                AxoupdateError::NetworkError {
+                    host: "Axo Releases".to_owned(),
+                    source: Box::new(e),
+                }
+            } else {
+                e.into()
+            }
+        })?;
 
-    let releases: Vec<Release> = our_release
-        .releases
+    let releases = release_lists
         .into_iter()
-        .filter_map(|r| Release::try_from_gazenot(r).ok())
+        .filter(|rl| app_names.contains(&rl.package_name))
+        .map(|rl| {
+            let releases = rl
+                .releases
+                .into_iter()
+                .filter_map(|r| {
+                    let tag = r.tag_name.clone();
+                    let release = Release::try_from_gazenot(r).ok();
+                    if release.is_none() {
+                        skipped_tags.record(tag);
+                    }
+                    release
+                })
+                .collect();
+            (rl.package_name, releases)
+        })
         .collect();
 
     Ok(releases)
 }
 
+/// Determines whether `error`, or any error it was caused by, is a failure
+/// to connect to the server or a timeout, rather than an error response.
+fn is_connection_failure(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+            if e.is_connect() || e.is_timeout() {
+                return true;
+            }
+        }
+        if let Some(e) = error.downcast_ref::<std::io::Error>() {
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::TimedOut
+            ) {
+                return true;
+            }
+        }
+        current = error.source();
+    }
+
+    false
+}
+
 impl Release {
     /// Constructs a release from Axo Releases data fetched via gazenot.
     pub(crate) fn try_from_gazenot(release: gazenot::PublicRelease) -> AxoupdateResult<Release> {
@@ -87,6 +240,60 @@ impl Release {
                 })
                 .collect(),
             prerelease: release.prerelease,
+            draft: false,
+            // gazenot doesn't expose publish dates or release notes yet
+            published_at: None,
+            body: None,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use axoasset::reqwest;
+
+    use super::{is_connection_failure, releases_for_app};
+    use crate::test::fixtures::release;
+    use crate::{AxoupdateError, Version};
+
+    #[test]
+    fn test_releases_for_app() {
+        let lists = HashMap::from([
+            ("axolotlsay".to_owned(), vec![release("1.0.0")]),
+            ("unreleased".to_owned(), vec![]),
+        ]);
+
+        let releases = releases_for_app("workspace", "axolotlsay", lists.clone()).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert!(matches!(
+            releases_for_app("workspace", "unreleased", lists.clone()),
+            Err(AxoupdateError::NoStableReleases { status: None, .. })
+        ));
+        assert!(matches!(
+            releases_for_app("workspace", "missing", lists),
+            Err(AxoupdateError::ReleaseNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_is_connection_failure() {
+        // Nothing listens on port 1, so connecting to it fails
+        let refused = reqwest::Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap()
+            .get("http://127.0.0.1:1")
+            .send()
+            .await
+            .unwrap_err();
+        assert!(is_connection_failure(&refused));
+
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(is_connection_failure(&reset));
+
+        let not_network = Version::parse("not a version").unwrap_err();
+        assert!(!is_connection_failure(&not_network));
+    }
+}
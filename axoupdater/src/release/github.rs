@@ -1,16 +1,28 @@
 //! Fetching and processing from GitHub Releases
 
-use super::{Asset, Release};
+use super::{
+    is_installer_name, latest_stable_of, Asset, LatestEtag, Release, ReleaseBackend, SkippedTags,
+};
 use crate::{app_name_to_env_var, errors::*};
 use axoasset::reqwest::{
     self,
-    header::{ACCEPT, USER_AGENT},
+    header::{ACCEPT, ETAG, IF_NONE_MATCH},
 };
 use axotag::{parse_tag, Version};
+use futures_util::{future::BoxFuture, stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::env;
+use std::{env, future::Future};
 use url::Url;
 
+/// The maximum number of pages of releases to fetch at once
+const MAX_CONCURRENT_PAGES: usize = 4;
+
+/// Overrides the GitHub API base URL for every app, if none of the
+/// app-specific variables are set. Unlike those, this is the API's URL
+/// itself rather than the URL of the GitHub instance; it's intended for
+/// pointing axoupdater at a mock server in tests.
+const GITHUB_API_BASE_ENV_VAR: &str = "AXOUPDATER_GITHUB_API_BASE";
+
 fn github_api(app_name: &str) -> AxoupdateResult<String> {
     let formatted_app_name = app_name_to_env_var(app_name);
     let ghe_env_var = format!("{}_INSTALLER_GHE_BASE_URL", formatted_app_name);
@@ -37,6 +49,8 @@ fn github_api(app_name: &str) -> AxoupdateResult<String> {
         };
         let port = parsed.port().map(|p| format!(":{p}")).unwrap_or_default();
         Ok(format!("{}://api.{}{}", parsed.scheme(), domain, port))
+    } else if let Ok(value) = env::var(GITHUB_API_BASE_ENV_VAR) {
+        Ok(value.trim_end_matches('/').to_owned())
     } else {
         Ok("https://api.github.com".to_string())
     }
@@ -55,6 +69,16 @@ pub struct GithubRelease {
     pub assets: Vec<GithubAsset>,
     /// Whether or not this release is a prerelease
     pub prerelease: bool,
+    /// Whether or not this release is an unpublished draft
+    #[serde(default)]
+    pub draft: bool,
+    /// When this release was published, as an ISO 8601 timestamp.
+    /// Drafts haven't been published yet.
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// The release notes, as Markdown
+    #[serde(default)]
+    pub body: Option<String>,
 }
 
 /// Represents a specific asset inside a GitHub Release.
@@ -68,33 +92,200 @@ pub struct GithubAsset {
     pub name: String,
 }
 
-pub(crate) async fn get_latest_github_release(
-    name: &str,
-    owner: &str,
-    app_name: &str,
-    token: &Option<String>,
-) -> AxoupdateResult<Option<Release>> {
-    let client = reqwest::Client::new();
+/// Who a request to the GitHub API is made on behalf of: the repository
+/// `owner/name`, the app whose installers we're looking for in it, and the
+/// token to authenticate with, if any.
+#[derive(Clone, Copy)]
+pub(crate) struct GithubRequest<'a> {
+    pub(crate) client: &'a reqwest::Client,
+    pub(crate) owner: &'a str,
+    pub(crate) name: &'a str,
+    pub(crate) app_name: &'a str,
+    pub(crate) installer_pattern: Option<&'a str>,
+    pub(crate) token: &'a Option<String>,
+}
+
+impl<'a> GithubRequest<'a> {
+    /// A request for `app_name`'s releases in `owner/name`, without a token
+    /// or custom installer pattern.
+    pub(crate) fn new(
+        client: &'a reqwest::Client,
+        owner: &'a str,
+        name: &'a str,
+        app_name: &'a str,
+    ) -> Self {
+        GithubRequest {
+            client,
+            owner,
+            name,
+            app_name,
+            installer_pattern: None,
+            token: &None,
+        }
+    }
+}
+
+/// The built-in backend for releases hosted on GitHub Releases
+pub(crate) struct GithubBackend {
+    pub(crate) client: reqwest::Client,
+    pub(crate) name: String,
+    pub(crate) owner: String,
+    pub(crate) app_name: String,
+    pub(crate) token: Option<String>,
+    pub(crate) skipped_tags: SkippedTags,
+    pub(crate) allow_non_semver_tags: bool,
+    pub(crate) installer_pattern: Option<String>,
+    pub(crate) prefer_api_latest: bool,
+    pub(crate) latest_etag: Option<LatestEtag>,
+}
+
+impl GithubBackend {
+    /// Hands the ETag of the response `release` was read from back to the
+    /// release cache, if it's enabled.
+    fn record_latest_etag(&self, etag: Option<String>, release: &Release) {
+        if let (Some(latest_etag), Some(etag)) = (&self.latest_etag, etag) {
+            latest_etag.set(Some((etag, release.clone())));
+        }
+    }
+
+    fn request(&self) -> GithubRequest<'_> {
+        GithubRequest {
+            client: &self.client,
+            owner: &self.owner,
+            name: &self.name,
+            app_name: &self.app_name,
+            installer_pattern: self.installer_pattern.as_deref(),
+            token: &self.token,
+        }
+    }
+}
+
+impl ReleaseBackend for GithubBackend {
+    fn list(&self) -> BoxFuture<'_, AxoupdateResult<Vec<Release>>> {
+        Box::pin(get_github_releases(self.request(), &self.skipped_tags))
+    }
+
+    fn latest_stable(&self) -> BoxFuture<'_, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move {
+            // GitHub has an API to request the latest stable release.
+            // This cuts down on our API requests compared to the paginated
+            // release list, which we only fall back to if there's no
+            // installable latest release. Other failures, like bad
+            // credentials or exhausted rate limits, would only recur there.
+            if self.prefer_api_latest {
+                // The release cache may have handed us the latest release as
+                // of an earlier response, to check whether it's still current
+                let known = self.latest_etag.as_ref().and_then(|latest| latest.take());
+                let etag = known.as_ref().map(|(etag, _)| etag.as_str());
+                let latest = get_latest_github_release_conditional(self.request(), etag).await;
+                match latest {
+                    Ok(ConditionalRelease::NotModified) => {
+                        if let Some((etag, release)) = known {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(tag = %release.tag_name, "latest release not modified since it was cached");
+                            self.record_latest_etag(Some(etag), &release);
+                            return Ok(Some(release));
+                        }
+                    }
+                    Ok(ConditionalRelease::Modified {
+                        release: Some(release),
+                        etag,
+                    }) => {
+                        self.record_latest_etag(etag, &release);
+                        return Ok(Some(release));
+                    }
+                    Ok(_)
+                    | Err(AxoupdateError::NoStableReleases {
+                        status: Some(404), ..
+                    }) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            Ok(latest_stable_of(self.list().await?))
+        })
+    }
+
+    /// Unlike listing releases, this fails if `tag` can't be parsed,
+    /// unless non-semver tags are allowed.
+    fn specific_tag<'a>(&'a self, tag: &'a str) -> BoxFuture<'a, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move {
+            get_specific_github_tag(self.request(), tag, self.allow_non_semver_tags)
+                .await
+                .map(Some)
+        })
+    }
+
+    fn specific_version<'a>(
+        &'a self,
+        version: &'a Version,
+    ) -> BoxFuture<'a, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move {
+            get_specific_github_version(self.request(), version, &self.skipped_tags)
+                .await
+                .map(Some)
+        })
+    }
+}
+
+/// The result of a conditional request for the latest release
+pub(crate) enum ConditionalRelease {
+    /// The latest release hasn't changed since the response with the ETag
+    /// we sent
+    NotModified,
+    /// The latest release, if it's installable, along with the response's
+    /// ETag, if any
+    Modified {
+        release: Option<Release>,
+        etag: Option<String>,
+    },
+}
+
+/// Fetches the latest release, sending `etag` as `If-None-Match` if
+/// provided. GitHub doesn't count 304 Not Modified responses against the
+/// API rate limit.
+pub(crate) async fn get_latest_github_release_conditional(
+    request: GithubRequest<'_>,
+    etag: Option<&str>,
+) -> AxoupdateResult<ConditionalRelease> {
+    let GithubRequest {
+        client,
+        owner,
+        name,
+        app_name,
+        installer_pattern,
+        token,
+    } = request;
     let api: String = github_api(app_name)?;
     let mut request = client
         .get(format!("{api}/repos/{owner}/{name}/releases/latest"))
-        .header(ACCEPT, "application/json")
-        .header(
-            USER_AGENT,
-            format!("axoupdate/{}", env!("CARGO_PKG_VERSION")),
-        );
+        .header(ACCEPT, "application/json");
     if let Some(token) = token {
         request = request.bearer_auth(token);
     }
-    let gh_release: GithubRelease = request
-        .send()
-        .await?
-        .error_for_status()
-        .map_err(|_| AxoupdateError::NoStableReleases {
-            app_name: app_name.to_owned(),
-        })?
-        .json()
-        .await?;
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let resp = request.send().await?;
+    if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(ConditionalRelease::NotModified);
+    }
+    if !resp.status().is_success() {
+        return Err(github_error(client, resp, token, |status, message| {
+            AxoupdateError::NoStableReleases {
+                app_name: app_name.to_owned(),
+                status: Some(status),
+                message,
+            }
+        })
+        .await);
+    }
+    let etag = resp
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned());
+    let gh_release: GithubRelease = resp.json().await?;
 
     // Ensure that this release contains an installer asset; if not, it may be
     // a mismarked "latest" release that's not installable by us.
@@ -102,130 +293,317 @@ pub(crate) async fn get_latest_github_release(
     if !gh_release
         .assets
         .iter()
-        .any(|asset| asset.name.starts_with(&format!("{app_name}-installer")))
+        .any(|asset| is_installer_name(app_name, installer_pattern, &asset.name))
     {
-        return Ok(None);
+        return Ok(ConditionalRelease::Modified {
+            release: None,
+            etag: None,
+        });
     }
 
-    match Release::try_from_github(app_name, gh_release) {
-        Ok(release) => Ok(Some(release)),
-        Err(e) => Err(e),
-    }
+    let release = Release::try_from_github(app_name, gh_release)?;
+
+    Ok(ConditionalRelease::Modified {
+        release: Some(release),
+        etag,
+    })
 }
 
 pub(crate) async fn get_specific_github_tag(
-    name: &str,
-    owner: &str,
-    app_name: &str,
+    request: GithubRequest<'_>,
     tag: &str,
-    token: &Option<String>,
+    allow_non_semver_tags: bool,
 ) -> AxoupdateResult<Release> {
-    let client = reqwest::Client::new();
+    let GithubRequest {
+        client,
+        owner,
+        name,
+        app_name,
+        token,
+        ..
+    } = request;
     let api: String = github_api(app_name)?;
     let mut request = client
         .get(format!("{api}/repos/{owner}/{name}/releases/tags/{tag}"))
-        .header(ACCEPT, "application/json")
-        .header(
-            USER_AGENT,
-            format!("axoupdate/{}", env!("CARGO_PKG_VERSION")),
-        );
+        .header(ACCEPT, "application/json");
     if let Some(token) = token {
         request = request.bearer_auth(token);
     }
-    let gh_release: GithubRelease = request
-        .send()
-        .await?
-        .error_for_status()
-        .map_err(|_| AxoupdateError::VersionNotFound {
-            name: name.to_owned(),
-            app_name: app_name.to_owned(),
-            version: tag.to_owned(),
-        })?
-        .json()
-        .await?;
+    let resp = request.send().await?;
+    if !resp.status().is_success() {
+        return Err(github_error(client, resp, token, |status, message| {
+            AxoupdateError::VersionNotFound {
+                name: name.to_owned(),
+                app_name: app_name.to_owned(),
+                version: tag.to_owned(),
+                status: Some(status),
+                message,
+            }
+        })
+        .await);
+    }
+    let gh_release: GithubRelease = resp.json().await?;
 
-    Release::try_from_github(app_name, gh_release)
+    Release::try_from_github_relaxed(app_name, gh_release, allow_non_semver_tags)
 }
 
 pub(crate) async fn get_specific_github_version(
-    name: &str,
-    owner: &str,
-    app_name: &str,
+    request: GithubRequest<'_>,
     version: &Version,
-    token: &Option<String>,
+    skipped_tags: &SkippedTags,
 ) -> AxoupdateResult<Release> {
-    let releases = get_github_releases(name, owner, app_name, token).await?;
+    // Stop paginating as soon as we've seen the version we're looking for
+    let releases = get_github_releases_until(request, skipped_tags, |releases| {
+        releases.iter().any(|r| &r.version == version)
+    })
+    .await?;
     let release = releases.into_iter().find(|r| &r.version == version);
 
     if let Some(release) = release {
         Ok(release)
     } else {
         Err(AxoupdateError::VersionNotFound {
-            name: name.to_owned(),
-            app_name: app_name.to_owned(),
+            name: request.name.to_owned(),
+            app_name: request.app_name.to_owned(),
             version: version.to_string(),
+            status: None,
+            message: None,
         })
     }
 }
 
 pub(crate) async fn get_github_releases(
-    name: &str,
-    owner: &str,
-    app_name: &str,
-    token: &Option<String>,
+    request: GithubRequest<'_>,
+    skipped_tags: &SkippedTags,
+) -> AxoupdateResult<Vec<Release>> {
+    get_github_releases_until(request, skipped_tags, |_| false).await
+}
+
+/// Fetches releases page by page, stopping early once `done` returns true
+/// for the releases fetched so far.
+async fn get_github_releases_until(
+    request: GithubRequest<'_>,
+    skipped_tags: &SkippedTags,
+    done: impl Fn(&[Release]) -> bool,
 ) -> AxoupdateResult<Vec<Release>> {
-    let client = reqwest::Client::new();
+    let GithubRequest {
+        client,
+        owner,
+        name,
+        app_name,
+        installer_pattern,
+        token,
+    } = request;
     let api: String = github_api(app_name)?;
-    let mut url = format!("{api}/repos/{owner}/{name}/releases");
-    let mut pages_remain = true;
-    let mut data: Vec<Release> = vec![];
-
-    while pages_remain {
-        // fetch the releases
-        let resp = get_releases(&client, &url, token).await?;
-
-        // collect the response headers
-        let headers = resp.headers();
-        let link_header = &headers
-            .get(reqwest::header::LINK)
-            .as_ref()
-            .map(|link_header_val| {
-                link_header_val
-                    .to_str()
-                    .expect("header was not ascii")
-                    .to_string()
-            });
+    let url = format!("{api}/repos/{owner}/{name}/releases");
 
-        // append the data
-        let mut body: Vec<Release> = resp
-            .json::<Vec<GithubRelease>>()
-            .await?
-            .into_iter()
-            .filter_map(|gh| Release::try_from_github(app_name, gh).ok())
-            .collect();
-        data.append(&mut body);
+    let data = paginate(
+        url,
+        |url| async move {
+            let (releases, link_header) = get_raw_release_page(client, &url, token).await?;
+            Ok((
+                releases_for_app(app_name, installer_pattern, releases, skipped_tags),
+                link_header,
+            ))
+        },
+        done,
+    )
+    .await?;
+
+    Ok(data)
+}
 
-        // check headers to see pages remain and if they do update the URL
-        pages_remain = if let Some(link_header) = link_header {
-            if link_header.contains("rel=\"next\"") {
-                url = get_next_url(link_header).expect("detected a next but it was a lie");
-                true
-            } else {
-                false
+/// Checks that the repository `owner/name` exists, and that the releases on
+/// its first page of releases include installers for `app_name`. This takes
+/// two requests regardless of how many releases there are.
+pub(crate) async fn validate_github_source(request: GithubRequest<'_>) -> AxoupdateResult<()> {
+    let GithubRequest {
+        client,
+        owner,
+        name,
+        app_name,
+        installer_pattern,
+        token,
+    } = request;
+    let api: String = github_api(app_name)?;
+    let mut request = client
+        .get(format!("{api}/repos/{owner}/{name}"))
+        .header(ACCEPT, "application/json");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let resp = request.send().await?;
+    if !resp.status().is_success() {
+        return Err(github_error(client, resp, token, |status, message| {
+            AxoupdateError::ReleaseSourceNotFound {
+                owner: owner.to_owned(),
+                name: name.to_owned(),
+                status: Some(status),
+                message,
             }
-        } else {
-            false
-        };
+        })
+        .await);
     }
 
-    Ok(data
+    let url = format!("{api}/repos/{owner}/{name}/releases");
+    let (releases, _) = get_raw_release_page(client, &url, token).await?;
+    let has_installers = releases.iter().any(|release| {
+        release
+            .assets
+            .iter()
+            .any(|asset| is_installer_name(app_name, installer_pattern, &asset.name))
+    });
+    if !has_installers {
+        return Err(AxoupdateError::NoInstallersFound {
+            app_name: app_name.to_owned(),
+            owner: owner.to_owned(),
+            name: name.to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Fetches every release in a repository, without interpreting them for any
+/// particular app. This lets releases be fetched once for several apps
+/// released from the same repository.
+pub(crate) async fn get_raw_github_releases(
+    request: GithubRequest<'_>,
+) -> AxoupdateResult<Vec<GithubRelease>> {
+    let GithubRequest {
+        client,
+        owner,
+        name,
+        app_name,
+        token,
+        ..
+    } = request;
+    let api: String = github_api(app_name)?;
+    let url = format!("{api}/repos/{owner}/{name}/releases");
+
+    paginate(
+        url,
+        |url| async move { get_raw_release_page(client, &url, token).await },
+        |_| false,
+    )
+    .await
+}
+
+/// Selects `app_name`'s installable releases from a page of releases; see
+/// `is_installer_name` for which assets count as installers. Releases whose
+/// tags can't be parsed are recorded in `skipped_tags` and left out.
+pub(crate) fn releases_for_app(
+    app_name: &str,
+    installer_pattern: Option<&str>,
+    releases: Vec<GithubRelease>,
+    skipped_tags: &SkippedTags,
+) -> Vec<Release> {
+    releases
         .into_iter()
-        .filter(|r| {
-            r.assets
+        .filter(|gh| {
+            gh.assets
                 .iter()
-                .any(|asset| asset.name.starts_with(&format!("{app_name}-installer")))
+                .any(|asset| is_installer_name(app_name, installer_pattern, &asset.name))
         })
-        .collect())
+        .filter_map(|gh| {
+            let tag = gh.tag_name.clone();
+            match Release::try_from_github(app_name, gh) {
+                Ok(release) => Some(release),
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(%tag, error = %_e, "skipping release with unparseable tag");
+                    skipped_tags.record(tag);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Fetches every page of a paginated listing starting at `url`, stopping
+/// early once `done` returns true for the items fetched so far.
+/// `fetch_page` returns a page's items along with its Link header, if any.
+async fn paginate<T, F, Fut>(
+    url: String,
+    fetch_page: F,
+    done: impl Fn(&[T]) -> bool,
+) -> AxoupdateResult<Vec<T>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = AxoupdateResult<(Vec<T>, Option<String>)>>,
+{
+    let (mut data, mut link_header) = fetch_page(url).await?;
+
+    // If GitHub told us how many pages there are, fetch the rest of them
+    // concurrently. `buffered` yields pages in their original order.
+    if let Some(last_url) = link_header.as_deref().and_then(get_last_url) {
+        if let Some(page_urls) = get_page_urls(&last_url) {
+            let mut pages = stream::iter(page_urls)
+                .map(&fetch_page)
+                .buffered(MAX_CONCURRENT_PAGES);
+
+            while !done(data.as_slice()) {
+                let Some(page) = pages.next().await else {
+                    break;
+                };
+                let (mut body, _) = page?;
+                data.append(&mut body);
+            }
+
+            link_header = None;
+        }
+    }
+
+    // Otherwise, follow the "next" links one at a time
+    while !done(data.as_slice()) {
+        let Some(link_header_value) = &link_header else {
+            break;
+        };
+        let Some(url) = get_next_url(link_header_value) else {
+            break;
+        };
+
+        let (mut body, next_link_header) = fetch_page(url).await?;
+        data.append(&mut body);
+        link_header = next_link_header;
+    }
+
+    Ok(data)
+}
+
+/// Fetches a single page of releases, returning the releases along with the
+/// page's Link header, if any. Drafts are left out.
+async fn get_raw_release_page(
+    client: &reqwest::Client,
+    url: &str,
+    token: &Option<String>,
+) -> AxoupdateResult<(Vec<GithubRelease>, Option<String>)> {
+    let resp = get_releases(client, url, token).await?;
+
+    // A malformed Link header just means we can't paginate any further;
+    // we'll return the releases we were able to fetch.
+    let link_header = resp
+        .headers()
+        .get(reqwest::header::LINK)
+        .and_then(|link_header_val| match link_header_val.to_str() {
+            Ok(value) => Some(value.to_string()),
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%url, "ignoring non-ASCII Link header; not fetching further pages");
+                None
+            }
+        });
+
+    let releases = resp
+        .json::<Vec<GithubRelease>>()
+        .await?
+        .into_iter()
+        // Drafts never have assets and may have arbitrary tags; skip them
+        // before we try to parse those tags.
+        .filter(|gh| !gh.draft)
+        .collect();
+
+    Ok((releases, link_header))
 }
 
 // The format of the header looks like so:
@@ -233,21 +611,53 @@ pub(crate) async fn get_github_releases(
 // <https://api.github.com/repositories/1300192/issues?page=2>; rel="prev", <https://api.github.com/repositories/1300192/issues?page=4>; rel="next", <https://api.github.com/repositories/1300192/issues?page=515>; rel="last", <https://api.github.com/repositories/1300192/issues?page=1>; rel="first"
 // ```
 fn get_next_url(link_header: &str) -> Option<String> {
+    get_link_url(link_header, "next")
+}
+
+fn get_last_url(link_header: &str) -> Option<String> {
+    get_link_url(link_header, "last")
+}
+
+fn get_link_url(link_header: &str, rel: &str) -> Option<String> {
     let links = link_header.split(',').collect::<Vec<_>>();
     for entry in links {
-        if entry.contains("next") {
-            let mut link = entry.split(';').collect::<Vec<_>>()[0]
-                .to_string()
-                .trim()
-                .to_string();
-            link.remove(0);
-            link.pop();
-            return Some(link);
+        if entry.contains(&format!("rel=\"{rel}\"")) {
+            let link = entry.split(';').next()?.trim();
+            let link = link.strip_prefix('<')?.strip_suffix('>')?;
+            return Some(link.to_string());
         }
     }
     None
 }
 
+/// Given the URL of the last page of results, returns the URLs of every page
+/// from page 2 through to that last page.
+fn get_page_urls(last_url: &str) -> Option<Vec<String>> {
+    let last_url = Url::parse(last_url).ok()?;
+    let last_page = last_url
+        .query_pairs()
+        .find(|(key, _)| key == "page")
+        .and_then(|(_, value)| value.parse::<u64>().ok())?;
+    let other_pairs: Vec<(String, String)> = last_url
+        .query_pairs()
+        .filter(|(key, _)| key != "page")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    let urls = (2..=last_page)
+        .map(|page| {
+            let mut url = last_url.clone();
+            url.query_pairs_mut()
+                .clear()
+                .extend_pairs(&other_pairs)
+                .append_pair("page", &page.to_string());
+            url.to_string()
+        })
+        .collect();
+
+    Some(urls)
+}
+
 pub(crate) async fn get_releases(
     client: &reqwest::Client,
     url: &str,
@@ -256,15 +666,102 @@ pub(crate) async fn get_releases(
     let mut request = client
         .get(url)
         .header(ACCEPT, "application/json")
-        .header(
-            USER_AGENT,
-            format!("axoupdate/{}", env!("CARGO_PKG_VERSION")),
-        )
         .header("X-GitHub-Api-Version", "2022-11-28");
     if let Some(token) = token {
         request = request.bearer_auth(token);
     }
-    Ok(request.send().await?.error_for_status()?)
+    let resp = request.send().await?;
+    if let Err(e) = resp.error_for_status_ref().map(|_| ()) {
+        return Err(github_error(client, resp, token, |_, _| e.into()).await);
+    }
+
+    Ok(resp)
+}
+
+/// The body GitHub returns alongside an unsuccessful status
+#[derive(Deserialize)]
+struct GithubErrorBody {
+    message: String,
+}
+
+/// Converts an unsuccessful response from the GitHub API into an error.
+/// Authentication failures become `AxoupdateError::Unauthorized`, and
+/// a 404 for a repository that `token` can't see becomes
+/// `AxoupdateError::PossiblyUnauthorized`; other failures are passed to
+/// `otherwise` along with the status and GitHub's error message.
+async fn github_error(
+    client: &reqwest::Client,
+    resp: reqwest::Response,
+    token: &Option<String>,
+    otherwise: impl FnOnce(u16, Option<String>) -> AxoupdateError,
+) -> AxoupdateError {
+    let status = resp.status();
+    let url = resp.url().clone();
+    // GitHub also uses 403 to report exhausted rate limits, which aren't
+    // a problem with the credentials themselves.
+    let rate_limited = resp
+        .headers()
+        .get("x-ratelimit-remaining")
+        .is_some_and(|remaining| remaining == "0");
+    let message = resp
+        .json::<GithubErrorBody>()
+        .await
+        .ok()
+        .map(|body| body.message);
+
+    if status == reqwest::StatusCode::UNAUTHORIZED
+        || (status == reqwest::StatusCode::FORBIDDEN && !rate_limited)
+    {
+        return AxoupdateError::Unauthorized {
+            status: status.as_u16(),
+            message,
+        };
+    }
+
+    if let Some(token) = token
+        .as_deref()
+        .filter(|_| status == reqwest::StatusCode::NOT_FOUND)
+    {
+        if repository_hidden(client, &url, token).await {
+            return AxoupdateError::PossiblyUnauthorized {
+                url: url.to_string(),
+                message,
+            };
+        }
+    }
+
+    otherwise(status.as_u16(), message)
+}
+
+/// Checks whether the repository that `url` belongs to is invisible with
+/// `token`. GitHub responds to requests for repositories a fine-grained
+/// token doesn't grant access to with 404 rather than 403, so as not to
+/// reveal that they exist; a 404 for the repository itself, rather than
+/// something in it, suggests that this is what's happened.
+async fn repository_hidden(client: &reqwest::Client, url: &Url, token: &str) -> bool {
+    let Some(segments) = url.path_segments() else {
+        return false;
+    };
+    let segments = segments.collect::<Vec<_>>();
+    // The repository's own URL is `{api}/repos/{owner}/{name}`
+    let Some(repo_path) = segments
+        .iter()
+        .position(|segment| *segment == "repos")
+        .and_then(|repos| segments.get(..repos + 3))
+    else {
+        return false;
+    };
+    let mut repo_url = url.clone();
+    repo_url.set_path(&format!("/{}", repo_path.join("/")));
+    repo_url.set_query(None);
+
+    client
+        .get(repo_url)
+        .header(ACCEPT, "application/json")
+        .bearer_auth(token)
+        .send()
+        .await
+        .is_ok_and(|resp| resp.status() == reqwest::StatusCode::NOT_FOUND)
 }
 
 impl Release {
@@ -273,20 +770,32 @@ impl Release {
         package_name: &str,
         release: GithubRelease,
     ) -> AxoupdateResult<Release> {
-        // try to parse the github release's tag using axotag
-        let announce = parse_tag(
-            &[axotag::Package {
-                name: package_name.to_owned(),
-                version: None,
-            }],
-            &release.tag_name,
-        )?;
-        let version = match announce.release {
-            axotag::ReleaseType::None => unreachable!("parse_tag should never return None"),
-            axotag::ReleaseType::Version(v) => v,
-            axotag::ReleaseType::Package { version, .. } => version,
+        let version = parse_github_tag(package_name, &release.tag_name)?;
+
+        Ok(Release::from_github_with_version(release, version))
+    }
+
+    /// Like `try_from_github`, but if `allow_non_semver_tags` is set, a
+    /// release whose tag can't be parsed as a version is given a synthetic
+    /// version, `0.0.0-sha.{tag}`, instead of being rejected. This lets
+    /// releases tagged with commit SHAs or names like `nightly` be
+    /// installed by their tag.
+    pub(crate) fn try_from_github_relaxed(
+        package_name: &str,
+        release: GithubRelease,
+        allow_non_semver_tags: bool,
+    ) -> AxoupdateResult<Release> {
+        let version = match parse_github_tag(package_name, &release.tag_name) {
+            Ok(version) => version,
+            Err(e) if allow_non_semver_tags => synthetic_version(&release.tag_name).ok_or(e)?,
+            Err(e) => return Err(e),
         };
-        Ok(Release {
+
+        Ok(Release::from_github_with_version(release, version))
+    }
+
+    fn from_github_with_version(release: GithubRelease, version: Version) -> Release {
+        Release {
             tag_name: release.tag_name,
             version,
             name: release.name,
@@ -301,22 +810,67 @@ impl Release {
                 })
                 .collect(),
             prerelease: release.prerelease,
-        })
+            draft: release.draft,
+            published_at: release.published_at,
+            body: release.body,
+        }
     }
 }
 
+/// Parses the version out of a release's tag using axotag.
+fn parse_github_tag(package_name: &str, tag: &str) -> AxoupdateResult<Version> {
+    let announce = parse_tag(
+        &[axotag::Package {
+            name: package_name.to_owned(),
+            version: None,
+        }],
+        tag,
+    )?;
+    let version = match announce.release {
+        axotag::ReleaseType::None => unreachable!("parse_tag should never return None"),
+        axotag::ReleaseType::Version(v) => v,
+        axotag::ReleaseType::Package { version, .. } => version,
+    };
+
+    Ok(version)
+}
+
+/// Builds the placeholder version given to releases whose tags aren't
+/// versions. Characters which aren't allowed in a prerelease identifier are
+/// replaced with `-`. Returns None if that still isn't a valid version,
+/// such as for all-numeric tags with leading zeroes.
+fn synthetic_version(tag: &str) -> Option<Version> {
+    let identifier: String = tag
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    Version::parse(&format!("0.0.0-sha.{identifier}")).ok()
+}
+
 #[cfg(test)]
 mod test {
     use super::{
-        get_github_releases, get_latest_github_release, get_next_url, get_specific_github_tag,
-        github_api, GithubAsset, GithubRelease,
+        get_github_releases, get_latest_github_release_conditional, get_next_url, get_page_urls,
+        get_specific_github_tag, github_api, synthetic_version, ConditionalRelease, GithubAsset,
+        GithubBackend, GithubRelease, GithubRequest, Release, SkippedTags,
     };
-    use axoasset::reqwest::StatusCode;
+    use crate::{AxoupdateError, AxoupdateResult, ReleaseBackend, Version};
+    use axoasset::reqwest::{self, StatusCode};
     use axoasset::serde_json::json;
     use httpmock::prelude::*;
     use serial_test::serial;
     use std::env;
 
+    async fn get_latest_github_release(
+        request: GithubRequest<'_>,
+    ) -> AxoupdateResult<Option<Release>> {
+        match get_latest_github_release_conditional(request, None).await? {
+            ConditionalRelease::Modified { release, .. } => Ok(release),
+            ConditionalRelease::NotModified => panic!("unconditional request wasn't modified"),
+        }
+    }
+
     #[test]
     fn test_link_header_parse() {
         let sample = r#"
@@ -349,6 +903,36 @@ mod test {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_link_header_parse_malformed() {
+        assert_eq!(get_next_url(r#"; rel="next""#), None);
+        assert_eq!(get_next_url(r#"https://example.com; rel="next""#), None);
+        assert_eq!(get_next_url(r#"<>; rel="next""#), Some(String::new()));
+    }
+
+    #[test]
+    fn test_page_urls() {
+        let result = get_page_urls(
+            "https://api.github.com/repositories/1300192/releases?per_page=30&page=4",
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            vec![
+                "https://api.github.com/repositories/1300192/releases?per_page=30&page=2",
+                "https://api.github.com/repositories/1300192/releases?per_page=30&page=3",
+                "https://api.github.com/repositories/1300192/releases?per_page=30&page=4",
+            ]
+        );
+
+        // A single page of results has no further pages to fetch
+        let result =
+            get_page_urls("https://api.github.com/repositories/1300192/releases?page=1").unwrap();
+        assert!(result.is_empty());
+
+        assert!(get_page_urls("https://api.github.com/repositories/1300192/releases").is_none());
+    }
+
     #[test]
     #[serial] // modifying the global state environment variables
     fn test_github_api_no_env_var() {
@@ -358,6 +942,21 @@ mod test {
         assert_eq!(result, "https://api.github.com");
     }
 
+    #[test]
+    #[serial] // modifying the global state environment variables
+    fn test_github_api_base_override() {
+        env::set_var("AXOUPDATER_GITHUB_API_BASE", "http://127.0.0.1:8000/");
+        let result = github_api("dist").unwrap();
+        // App-specific settings still take precedence
+        env::set_var("DIST_INSTALLER_GITHUB_BASE_URL", "https://magic.com");
+        let app_specific = github_api("dist").unwrap();
+        env::remove_var("DIST_INSTALLER_GITHUB_BASE_URL");
+        env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        assert_eq!(result, "http://127.0.0.1:8000");
+        assert_eq!(app_specific, "https://api.magic.com");
+    }
+
     #[test]
     #[serial] // modifying the global state environment variables
     fn test_github_api_overwrite() {
@@ -425,11 +1024,67 @@ mod test {
         assert_eq!(result, "https://127.0.0.1/api/v3");
     }
 
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_get_latest_github_release_errors() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+        let client = reqwest::Client::new();
+
+        let mut missing_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest");
+                then.status(StatusCode::NOT_FOUND.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Not Found"}));
+            })
+            .await;
+        let missing =
+            get_latest_github_release(GithubRequest::new(&client, "owner", "name", "app")).await;
+        missing_call.assert();
+        missing_call.delete_async().await;
+
+        let unauthorized_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest");
+                then.status(StatusCode::UNAUTHORIZED.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Bad credentials"}));
+            })
+            .await;
+        let token = Some("token".to_owned());
+        let unauthorized = get_latest_github_release(GithubRequest {
+            token: &token,
+            ..GithubRequest::new(&client, "owner", "name", "app")
+        })
+        .await;
+        unauthorized_call.assert();
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        assert!(matches!(
+            missing,
+            Err(AxoupdateError::NoStableReleases {
+                status: Some(404),
+                ..
+            })
+        ));
+        match unauthorized {
+            Err(AxoupdateError::Unauthorized { status, message }) => {
+                assert_eq!(status, 401);
+                assert_eq!(message.as_deref(), Some("Bad credentials"));
+            }
+            other => panic!("expected an Unauthorized error, got {other:?}"),
+        }
+    }
+
     #[tokio::test]
     #[serial] // modifying the global state environment variables
     async fn test_get_latest_github_release_custom_endpoint() {
         let server = MockServer::start_async().await;
         env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+        let client = reqwest::Client::new();
 
         let latest_release_http_call = server
             .mock_async(|when, then| {
@@ -441,7 +1096,8 @@ mod test {
             })
             .await;
 
-        let result = get_latest_github_release("name", "owner", "app", &None).await;
+        let result =
+            get_latest_github_release(GithubRequest::new(&client, "owner", "name", "app")).await;
         env::remove_var("APP_INSTALLER_GHE_BASE_URL");
 
         assert!(result.is_ok());
@@ -450,17 +1106,160 @@ mod test {
         latest_release_http_call.assert();
     }
 
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_possibly_unauthorized() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+
+        server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path_contains("/api/v3/repos/owner/")
+                    .path_contains("/releases/tags/v9.9.9");
+                then.status(StatusCode::NOT_FOUND.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Not Found"}));
+            })
+            .await;
+        // The token can see this repository, but not the other one
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/visible");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"name": "visible"}));
+            })
+            .await;
+        let hidden_repo_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/hidden");
+                then.status(StatusCode::NOT_FOUND.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Not Found"}));
+            })
+            .await;
+
+        let client = reqwest::Client::new();
+        let token = Some("token".to_owned());
+        let no_token = None;
+        let fetch = |name, token| {
+            let request = GithubRequest {
+                token,
+                ..GithubRequest::new(&client, "owner", name, "app")
+            };
+            get_specific_github_tag(request, "v9.9.9", false)
+        };
+        let missing_tag = fetch("visible", &token).await;
+        let hidden = fetch("hidden", &token).await;
+        let unauthenticated = fetch("hidden", &no_token).await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        assert!(matches!(
+            missing_tag,
+            Err(AxoupdateError::VersionNotFound {
+                status: Some(404),
+                ..
+            })
+        ));
+        match hidden {
+            Err(AxoupdateError::PossiblyUnauthorized { url, .. }) => {
+                assert!(url.ends_with("/api/v3/repos/owner/hidden/releases/tags/v9.9.9"));
+            }
+            other => panic!("expected a PossiblyUnauthorized error, got {other:?}"),
+        }
+        // Without a token, there's nothing to blame the 404 on
+        assert!(matches!(
+            unauthenticated,
+            Err(AxoupdateError::VersionNotFound { .. })
+        ));
+        hidden_repo_call.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_latest_stable_fallback() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+        let backend = GithubBackend {
+            client: reqwest::Client::new(),
+            name: "name".to_owned(),
+            owner: "owner".to_owned(),
+            app_name: "app".to_owned(),
+            token: None,
+            skipped_tags: SkippedTags::default(),
+            allow_non_semver_tags: false,
+            installer_pattern: None,
+            prefer_api_latest: true,
+            latest_etag: None,
+        };
+
+        let releases_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/name/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!(vec![build_test_git_hub_release()]));
+            })
+            .await;
+
+        // GitHub reports that there's no latest stable release with a 404
+        let mut missing_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest");
+                then.status(StatusCode::NOT_FOUND.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Not Found"}));
+            })
+            .await;
+        let missing = backend.latest_stable().await;
+        missing_call.delete_async().await;
+        let hits_after_missing = releases_call.hits_async().await;
+
+        server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/latest");
+                then.status(StatusCode::UNAUTHORIZED.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Bad credentials"}));
+            })
+            .await;
+        let unauthorized = backend.latest_stable().await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        assert_eq!(
+            missing.unwrap().unwrap().version,
+            Version::parse("1.0.0").unwrap()
+        );
+        assert_eq!(hits_after_missing, 1);
+        // Other errors are reported without listing releases
+        assert!(matches!(
+            unauthorized,
+            Err(AxoupdateError::Unauthorized { status: 401, .. })
+        ));
+        releases_call.assert_hits_async(1).await;
+    }
+
     fn build_test_git_hub_release() -> GithubRelease {
+        build_test_git_hub_release_with_tag("1.0.0")
+    }
+
+    fn build_test_git_hub_release_with_tag(tag: &str) -> GithubRelease {
         GithubRelease {
-            tag_name: String::from("1.0.0"),
+            tag_name: String::from(tag),
             name: String::from("n"),
             url: String::from("u"),
             assets: vec![GithubAsset {
                 url: String::from("un"),
                 browser_download_url: String::from("bdu"),
-                name: String::from("app-installer"),
+                name: String::from("app-installer.sh"),
             }],
             prerelease: false,
+            draft: false,
+            published_at: None,
+            body: None,
         }
     }
 
@@ -480,7 +1279,9 @@ mod test {
             })
             .await;
 
-        let result = get_specific_github_tag("name", "owner", "app", "1.0.0", &None).await;
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let result = get_specific_github_tag(request, "1.0.0", false).await;
         env::remove_var("APP_INSTALLER_GHE_BASE_URL");
 
         assert!(result.is_ok());
@@ -503,11 +1304,254 @@ mod test {
             })
             .await;
 
-        let result = get_github_releases("name", "owner", "app", &None).await;
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let result = get_github_releases(request, &SkippedTags::default()).await;
         env::remove_var("APP_INSTALLER_GHE_BASE_URL");
 
         assert!(result.is_ok());
 
         releases_http_call.assert();
     }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_get_github_releases_paginated() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+
+        let page_url =
+            |page: u32| server.url(format!("/api/v3/repos/owner/name/releases?page={page}"));
+        let link_header = format!(
+            r#"<{}>; rel="next", <{}>; rel="last""#,
+            page_url(2),
+            page_url(3)
+        );
+
+        // Mocks are matched in the order they're defined, so the specific
+        // pages need to come before the unqualified first page.
+        let page_two_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases")
+                    .query_param("page", "2");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!(vec![build_test_git_hub_release_with_tag("0.9.0")]));
+            })
+            .await;
+        let page_three_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases")
+                    .query_param("page", "3");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!(vec![build_test_git_hub_release_with_tag("0.8.0")]));
+            })
+            .await;
+        let first_page_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/name/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .header("link", &link_header)
+                    .json_body(json!(vec![build_test_git_hub_release()]));
+            })
+            .await;
+
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let result = get_github_releases(request, &SkippedTags::default()).await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        let versions: Vec<String> = result
+            .unwrap()
+            .into_iter()
+            .map(|r| r.version.to_string())
+            .collect();
+        assert_eq!(versions, vec!["1.0.0", "0.9.0", "0.8.0"]);
+
+        first_page_call.assert();
+        page_two_call.assert();
+        page_three_call.assert();
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_get_github_releases_skips_drafts() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+
+        let mut draft = build_test_git_hub_release_with_tag("2.0.0");
+        draft.draft = true;
+        let mut malformed_draft = build_test_git_hub_release_with_tag("not a version");
+        malformed_draft.draft = true;
+        malformed_draft.assets = vec![];
+
+        let releases_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/name/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!(vec![
+                        malformed_draft,
+                        draft,
+                        build_test_git_hub_release()
+                    ]));
+            })
+            .await;
+
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let result = get_github_releases(request, &SkippedTags::default()).await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        let versions: Vec<String> = result
+            .unwrap()
+            .into_iter()
+            .map(|r| r.version.to_string())
+            .collect();
+        assert_eq!(versions, vec!["1.0.0"]);
+
+        releases_call.assert();
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_get_github_releases_records_skipped_tags() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+
+        let releases_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/name/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!(vec![
+                        build_test_git_hub_release_with_tag("not a version"),
+                        build_test_git_hub_release()
+                    ]));
+            })
+            .await;
+
+        let skipped_tags = SkippedTags::default();
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let result = get_github_releases(request, &skipped_tags).await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        // The bad tag doesn't stop us from listing the rest of the releases
+        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(skipped_tags.get(), vec!["not a version".to_owned()]);
+
+        releases_call.assert();
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_get_github_releases_non_ascii_link_header() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+
+        let link_header = format!(
+            r#"<{}>; rel="next", <ñ>; rel="last""#,
+            server.url("/api/v3/repos/owner/name/releases?page=2")
+        );
+        let first_page_call = server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/v3/repos/owner/name/releases");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .header("link", &link_header)
+                    .json_body(json!(vec![build_test_git_hub_release()]));
+            })
+            .await;
+
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let result = get_github_releases(request, &SkippedTags::default()).await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        // We can't paginate, but we still get the first page
+        let versions: Vec<String> = result
+            .unwrap()
+            .into_iter()
+            .map(|r| r.version.to_string())
+            .collect();
+        assert_eq!(versions, vec!["1.0.0"]);
+
+        first_page_call.assert();
+    }
+
+    #[test]
+    fn test_release_notes() {
+        let mut gh_release = json!({
+            "tag_name": "v1.0.0",
+            "name": "v1.0.0",
+            "url": "u",
+            "assets": [],
+            "prerelease": false,
+            "body": "## What's new\n\n* Everything",
+        });
+        let parsed: GithubRelease = axoasset::serde_json::from_value(gh_release.clone()).unwrap();
+        let release = Release::try_from_github("app", parsed).unwrap();
+        assert_eq!(
+            release.body.as_deref(),
+            Some("## What's new\n\n* Everything")
+        );
+
+        // Releases without notes may leave it out entirely
+        gh_release.as_object_mut().unwrap().remove("body");
+        let parsed: GithubRelease = axoasset::serde_json::from_value(gh_release).unwrap();
+        assert!(Release::try_from_github("app", parsed)
+            .unwrap()
+            .body
+            .is_none());
+    }
+
+    #[test]
+    fn test_synthetic_version() {
+        let version = |tag: &str| synthetic_version(tag).map(|v| v.to_string());
+
+        assert_eq!(version("nightly"), Some("0.0.0-sha.nightly".to_owned()));
+        assert_eq!(version("3f2a9c1e"), Some("0.0.0-sha.3f2a9c1e".to_owned()));
+        assert_eq!(
+            version("nightly/2024.05.01"),
+            Some("0.0.0-sha.nightly-2024-05-01".to_owned())
+        );
+        // Numeric identifiers can't have leading zeroes
+        assert_eq!(version("0123"), None);
+    }
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_get_specific_github_tag_non_semver() {
+        let server = MockServer::start_async().await;
+        env::set_var("APP_INSTALLER_GHE_BASE_URL", server.base_url());
+
+        let _release_tag_http_call = server
+            .mock_async(|when, then| {
+                when.method("GET")
+                    .path("/api/v3/repos/owner/name/releases/tags/nightly");
+                then.status(StatusCode::OK.as_u16())
+                    .header("content-type", "application/json")
+                    .json_body(json!(build_test_git_hub_release_with_tag("nightly")));
+            })
+            .await;
+
+        let client = reqwest::Client::new();
+        let request = GithubRequest::new(&client, "owner", "name", "app");
+        let fetch = |allow_non_semver_tags| {
+            get_specific_github_tag(request, "nightly", allow_non_semver_tags)
+        };
+        let strict = fetch(false).await;
+        let relaxed = fetch(true).await;
+        env::remove_var("APP_INSTALLER_GHE_BASE_URL");
+
+        assert!(strict.is_err());
+        let relaxed = relaxed.unwrap();
+        assert_eq!(relaxed.tag_name, "nightly");
+        assert_eq!(relaxed.version.to_string(), "0.0.0-sha.nightly");
+    }
 }
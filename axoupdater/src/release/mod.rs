@@ -1,6 +1,12 @@
-use std::fmt;
+use std::{
+    cmp::Ordering,
+    fmt,
+    sync::{Arc, Mutex},
+};
 
-use serde::Deserialize;
+use axoasset::reqwest;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
 
 use crate::{errors::*, AuthorizationTokens, AxoUpdater, UpdateRequest, Version};
 
@@ -10,7 +16,7 @@ pub(crate) mod axodotdev;
 pub(crate) mod github;
 
 /// A struct representing a specific release, either from GitHub or Axo Releases.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Release {
     /// The tag this release represents
     pub tag_name: String,
@@ -24,10 +30,19 @@ pub struct Release {
     pub assets: Vec<Asset>,
     /// Whether or not this release is a prerelease
     pub prerelease: bool,
+    /// Whether or not this release is an unpublished draft
+    pub draft: bool,
+    /// When this release was published, as an ISO 8601 timestamp, if the
+    /// release source provides it
+    #[serde(default)]
+    pub published_at: Option<String>,
+    /// The release notes, as Markdown, if the release source provides them
+    #[serde(default)]
+    pub body: Option<String>,
 }
 
 /// Represents a specific asset inside a release.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Asset {
     /// The URL at which this asset can be found
     pub url: String,
@@ -37,8 +52,84 @@ pub struct Asset {
     pub name: String,
 }
 
+impl Release {
+    /// Returns this release's assets whose names match the glob `pattern`,
+    /// in the order the release lists them. In the pattern, `*` matches any
+    /// run of characters and `?` matches any single character; everything
+    /// else must match exactly. For example, `*.sig` matches every
+    /// signature, and `completions-*.zip` every completions archive.
+    pub fn find_assets(&self, pattern: &str) -> Vec<&Asset> {
+        self.assets
+            .iter()
+            .filter(|asset| glob_matches(pattern, &asset.name))
+            .collect()
+    }
+
+    /// Returns this release's assets whose names match the regular
+    /// expression `pattern`, in the order the release lists them. Like
+    /// `Regex::is_match`, the pattern may match any part of the name unless
+    /// it's anchored with `^` and `$`.
+    #[cfg(feature = "regex")]
+    pub fn find_assets_regex(&self, pattern: &regex::Regex) -> Vec<&Asset> {
+        self.assets
+            .iter()
+            .filter(|asset| pattern.is_match(&asset.name))
+            .collect()
+    }
+
+    /// Returns the first of this release's assets whose name matches the
+    /// glob `pattern`, if any.
+    pub(crate) fn find_asset(&self, pattern: &str) -> Option<&Asset> {
+        self.find_assets(pattern).into_iter().next()
+    }
+}
+
+/// Records the tags of releases which were skipped while listing releases
+/// because they couldn't be parsed as versions. Clones share the same
+/// record, so a backend can report them back to the updater that built it.
+#[derive(Clone, Default)]
+pub(crate) struct SkippedTags(Arc<Mutex<Vec<String>>>);
+
+impl SkippedTags {
+    pub(crate) fn record(&self, tag: String) {
+        if let Ok(mut tags) = self.0.lock() {
+            tags.push(tag);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut tags) = self.0.lock() {
+            tags.clear();
+        }
+    }
+
+    pub(crate) fn get(&self) -> Vec<String> {
+        self.0.lock().map(|tags| tags.clone()).unwrap_or_default()
+    }
+}
+
+/// Carries the ETag of a response for the latest release, along with the
+/// release it described, between the release cache and the GitHub backend.
+/// Before a lookup, it holds what was cached, so that the backend can ask
+/// whether the latest release has changed since; afterwards, it holds what
+/// the backend got back. Clones share the same value.
+#[derive(Clone, Default)]
+pub(crate) struct LatestEtag(Arc<Mutex<Option<(String, Release)>>>);
+
+impl LatestEtag {
+    pub(crate) fn set(&self, latest: Option<(String, Release)>) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = latest;
+        }
+    }
+
+    pub(crate) fn take(&self) -> Option<(String, Release)> {
+        self.0.lock().ok().and_then(|mut current| current.take())
+    }
+}
+
 /// Where service this app's releases are hosted on
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ReleaseSourceType {
     /// GitHub Releases
@@ -58,7 +149,7 @@ impl fmt::Display for ReleaseSourceType {
 }
 
 /// Information about the source of this app's releases
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ReleaseSource {
     /// Which hosting service to query for new releases
     pub release_type: ReleaseSourceType,
@@ -82,6 +173,7 @@ impl AxoUpdater {
     }
 
     /// Configures AxoUpdater to use a specific Axo Releases token when performing requests.
+    /// This is necessary to access private releases.
     pub fn set_axo_token(&mut self, token: &str) -> &mut AxoUpdater {
         self.tokens.axodotdev = Some(token.to_owned());
 
@@ -89,213 +181,1177 @@ impl AxoUpdater {
     }
 
     pub(crate) async fn fetch_release(&mut self) -> AxoupdateResult<()> {
+        let release = match self.cached_release() {
+            Some(release) => release,
+            None => {
+                self.prepare_revalidation();
+                let fetched = self.peek_latest_release().await;
+                let etag = self.latest_etag.take().map(|(etag, _)| etag);
+                let release = fetched?;
+                self.store_cached_release(&release, etag);
+                release
+            }
+        };
+        self.requested_release = Some(release);
+
+        Ok(())
+    }
+
+    /// Fetches the release this updater would install, without caching it.
+    ///
+    /// Unlike `query_new_version` or `is_update_needed`, this only needs
+    /// a shared reference, so it can be called from contexts where the
+    /// updater is shared (for example, behind an `Arc`) or where the
+    /// caller doesn't want the result to affect a later `run`.
+    pub async fn peek_latest_release(&self) -> AxoupdateResult<Release> {
         let Some(app_name) = &self.name else {
             return Err(AxoupdateError::NotConfigured {
                 missing_field: "app_name".to_owned(),
             });
         };
+
+        self.skipped_tags.clear();
+
+        if let Some(backend) = &self.custom_backend {
+            return self
+                .cancellable(fetch_release_for(
+                    backend.as_ref(),
+                    app_name,
+                    app_name,
+                    &self.version_specifier,
+                    self.current_version.as_ref(),
+                    &self.skip_versions,
+                ))
+                .await;
+        }
+
         let Some(source) = &self.source else {
             return Err(AxoupdateError::NotConfigured {
                 missing_field: "source".to_owned(),
             });
         };
 
-        let release = match self.version_specifier.to_owned() {
-            UpdateRequest::Latest => {
-                get_latest_stable_release(
-                    &source.name,
-                    &source.owner,
-                    &source.app_name,
-                    &source.release_type,
-                    &self.tokens,
-                )
-                .await?
-            }
-            UpdateRequest::LatestMaybePrerelease => {
-                get_latest_maybe_prerelease(
-                    &source.name,
-                    &source.owner,
-                    &source.app_name,
-                    &source.release_type,
-                    &self.tokens,
-                )
-                .await?
-            }
-            UpdateRequest::SpecificTag(version) => {
-                get_specific_tag(
-                    &source.name,
-                    &source.owner,
-                    &source.app_name,
-                    &source.release_type,
-                    &version,
-                    &self.tokens,
-                )
-                .await?
-            }
-            UpdateRequest::SpecificVersion(version) => {
-                get_specific_version(
-                    &source.name,
-                    &source.owner,
-                    &source.app_name,
-                    &source.release_type,
-                    &version.parse::<Version>()?,
-                    &self.tokens,
-                )
-                .await?
+        if self.fallback_sources.is_empty() {
+            return self.peek_release_from(source, app_name, true).await;
+        }
+
+        let mut errors = vec![];
+        let sources = std::iter::once((source, true))
+            .chain(self.fallback_sources.iter().map(|source| (source, false)));
+        for (source, primary) in sources {
+            match self.peek_release_from(source, app_name, primary).await {
+                Ok(release) => return Ok(release),
+                Err(e) if is_source_failure(&e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        owner = %source.owner,
+                        name = %source.name,
+                        error = %e,
+                        "release source failed; trying the next one"
+                    );
+                    errors.push(e);
+                }
+                Err(e) => return Err(e),
             }
+        }
+
+        Err(AxoupdateError::AllSourcesFailed { errors })
+    }
+
+    /// Fetches the release this updater would install from `source`. Only
+    /// the `primary` source's latest release is cached, so only it is
+    /// revalidated.
+    async fn peek_release_from(
+        &self,
+        source: &ReleaseSource,
+        app_name: &str,
+        primary: bool,
+    ) -> AxoupdateResult<Release> {
+        #[cfg(feature = "tracing")]
+        if self.no_proxy && source.release_type == ReleaseSourceType::Axo {
+            tracing::warn!("proxies can't be disabled for Axo Releases; using the environment's proxy settings");
+        } else if self.proxy.is_some() && source.release_type == ReleaseSourceType::Axo {
+            tracing::warn!("proxies can't be configured for Axo Releases; using the environment's proxy settings");
+        }
+
+        let mut options = self.backend_options();
+        if primary && self.release_cache_ttl.is_some() {
+            options.latest_etag = Some(self.latest_etag.clone());
+        }
+        self.cancellable(fetch_release_from(
+            self.http_client()?,
+            source,
+            app_name,
+            &options,
+            &self.version_specifier,
+            self.current_version.as_ref(),
+            &self.skip_versions,
+        ))
+        .await
+    }
+
+    /// Configures release sources to try, in order, if the primary source
+    /// fails because of a network error, a rate limit or a server error.
+    /// This can keep updates working during an outage of the primary host,
+    /// for example by falling back from GitHub to Axo Releases or a mirror.
+    /// If every source fails, `AxoupdateError::AllSourcesFailed` reports
+    /// each of their errors. Other errors, such as the requested version not
+    /// existing, are returned straight away without trying the fallbacks.
+    pub fn set_fallback_sources(&mut self, sources: Vec<ReleaseSource>) -> &mut AxoUpdater {
+        self.fallback_sources = sources;
+
+        self
+    }
+
+    /// Lists every release of this app from its release source.
+    pub(crate) async fn list_releases(&self) -> AxoupdateResult<Vec<Release>> {
+        if let Some(backend) = &self.custom_backend {
+            return self.cancellable(backend.list()).await;
+        }
+
+        let Some(source) = &self.source else {
+            return Err(AxoupdateError::NotConfigured {
+                missing_field: "source".to_owned(),
+            });
         };
 
-        let Some(release) = release else {
-            return Err(AxoupdateError::NoStableReleases {
-                app_name: app_name.to_owned(),
+        self.skipped_tags.clear();
+        let backend = builtin_backend(self.http_client()?, source, &self.backend_options())?;
+        self.cancellable(backend.list()).await
+    }
+
+    /// The settings the built-in backends should use for this updater.
+    pub(crate) fn backend_options(&self) -> BackendOptions<'_> {
+        BackendOptions {
+            tokens: &self.tokens,
+            skipped_tags: self.skipped_tags.clone(),
+            allow_non_semver_tags: self.allow_non_semver_tags,
+            installer_pattern: self.installer_pattern.as_deref(),
+            prefer_api_latest: self.prefer_api_latest,
+            latest_etag: None,
+        }
+    }
+
+    /// Checks that the release source is configured correctly: that its
+    /// repository exists and can be read, and that its releases include
+    /// installers for this app. This is a quick preflight check for sources
+    /// read from a receipt or supplied by the user; unlike fetching a
+    /// release, it only makes a couple of requests.
+    ///
+    /// Configuration problems are reported as
+    /// `AxoupdateError::ReleaseSourceNotFound`,
+    /// `AxoupdateError::NoInstallersFound` or, for credentials,
+    /// `AxoupdateError::Unauthorized`; network failures are reported as they
+    /// are elsewhere. For Axo Releases, this checks that the app has
+    /// releases at all.
+    pub async fn validate_source(&mut self) -> AxoupdateResult<()> {
+        let Some(source) = &self.source else {
+            return Err(AxoupdateError::NotConfigured {
+                missing_field: "source".to_owned(),
             });
         };
 
-        self.requested_release = Some(release);
+        match source.release_type {
+            #[cfg(feature = "github_releases")]
+            ReleaseSourceType::GitHub => {
+                let client = self.http_client()?;
+                github::validate_github_source(github::GithubRequest {
+                    installer_pattern: self.installer_pattern.as_deref(),
+                    token: &self.tokens.github,
+                    ..github::GithubRequest::new(
+                        &client,
+                        &source.owner,
+                        &source.name,
+                        &source.app_name,
+                    )
+                })
+                .await
+            }
+            #[cfg(not(feature = "github_releases"))]
+            ReleaseSourceType::GitHub => Err(AxoupdateError::BackendDisabled {
+                backend: "github".to_owned(),
+            }),
+            #[cfg(feature = "axo_releases")]
+            ReleaseSourceType::Axo => axodotdev::get_axo_releases(
+                &source.name,
+                &source.owner,
+                &source.app_name,
+                &self.tokens.axodotdev,
+                &SkippedTags::default(),
+            )
+            .await
+            .map(|_| ()),
+            #[cfg(not(feature = "axo_releases"))]
+            ReleaseSourceType::Axo => Err(AxoupdateError::BackendDisabled {
+                backend: "axodotdev".to_owned(),
+            }),
+        }
+    }
 
-        Ok(())
+    #[cfg(feature = "blocking")]
+    /// Identical to Axoupdater::validate_source(), but performed synchronously.
+    pub fn validate_source_sync(&mut self) -> AxoupdateResult<()> {
+        crate::block_on(self.validate_source())?
+    }
+
+    /// Returns the tags of any releases which were skipped during the last
+    /// release lookup because their tags couldn't be parsed as versions.
+    /// Listing releases tolerates these so that one bad tag doesn't break
+    /// updates, but this can help diagnose why a release wasn't found.
+    ///
+    /// Looking up a specific tag doesn't skip it; if that tag can't be
+    /// parsed, the lookup fails with an error instead.
+    pub fn last_skipped_tags(&self) -> Vec<String> {
+        self.skipped_tags.get()
+    }
+
+    /// Configures AxoUpdater to fetch releases from a custom backend instead
+    /// of the GitHub or Axo Releases backend named by the release source.
+    pub fn set_custom_backend(&mut self, backend: Box<dyn ReleaseBackend>) -> &mut AxoUpdater {
+        self.custom_backend = Some(backend);
+
+        self
     }
 }
 
-pub(crate) async fn get_specific_version(
-    name: &str,
-    owner: &str,
+/// A service which hosts an app's releases.
+///
+/// The built-in GitHub and Axo Releases support is implemented in terms of
+/// this trait; implement it and pass it to `AxoUpdater::set_custom_backend`
+/// in order to fetch releases from somewhere else. Only `list` needs to be
+/// implemented. The other methods search its results by default, and can be
+/// overridden if the backend has a cheaper way to answer them.
+pub trait ReleaseBackend: Send + Sync {
+    /// Lists every release of this app.
+    fn list(&self) -> BoxFuture<'_, AxoupdateResult<Vec<Release>>>;
+
+    /// Returns the latest stable release, if there is one.
+    fn latest_stable(&self) -> BoxFuture<'_, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move { Ok(latest_stable_of(self.list().await?)) })
+    }
+
+    /// Returns the latest release, including prereleases, if there is one.
+    fn latest_prerelease(&self) -> BoxFuture<'_, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move { Ok(latest_of(self.list().await?)) })
+    }
+
+    /// Returns the release with the tag `tag`, if there is one.
+    fn specific_tag<'a>(&'a self, tag: &'a str) -> BoxFuture<'a, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move { Ok(self.list().await?.into_iter().find(|r| r.tag_name == tag)) })
+    }
+
+    /// Returns the release with the version `version`, if there is one.
+    fn specific_version<'a>(
+        &'a self,
+        version: &'a Version,
+    ) -> BoxFuture<'a, AxoupdateResult<Option<Release>>> {
+        Box::pin(async move {
+            Ok(self
+                .list()
+                .await?
+                .into_iter()
+                .find(|r| &r.version == version))
+        })
+    }
+}
+
+/// Looks up the version of the release `specifier` selects from `source`,
+/// without an install receipt or a configured `AxoUpdater`. This is the
+/// same lookup `AxoUpdater::query_new_version` performs, with the default
+/// settings and no release cache, which makes it convenient for tools that
+/// only want to report whether a newer version exists.
+///
+/// `token` is used to authorize requests to whichever service `source` is
+/// hosted on. Returns None if the source has no release matching
+/// `specifier`. `UpdateRequest::LatestWithinMajor` is relative to an
+/// installed version, so it isn't supported here.
+pub async fn latest_version(
+    source: &ReleaseSource,
+    specifier: &UpdateRequest,
+    token: Option<&str>,
+) -> AxoupdateResult<Option<Version>> {
+    let token = token.map(|token| token.to_owned());
+    let tokens = match source.release_type {
+        ReleaseSourceType::GitHub => AuthorizationTokens {
+            github: token,
+            axodotdev: None,
+        },
+        ReleaseSourceType::Axo => AuthorizationTokens {
+            github: None,
+            axodotdev: token,
+        },
+    };
+    let options = BackendOptions {
+        tokens: &tokens,
+        skipped_tags: SkippedTags::default(),
+        allow_non_semver_tags: false,
+        installer_pattern: None,
+        prefer_api_latest: true,
+        latest_etag: None,
+    };
+    let client = crate::build_http_client(
+        reqwest::Client::builder().user_agent(crate::default_user_agent()),
+    )?;
+
+    match fetch_release_from(
+        client,
+        source,
+        &source.app_name,
+        &options,
+        specifier,
+        None,
+        &[],
+    )
+    .await
+    {
+        Ok(release) => Ok(Some(release.version)),
+        Err(AxoupdateError::NoStableReleases { status: None, .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(feature = "blocking")]
+/// Identical to latest_version(), but performed synchronously.
+pub fn latest_version_sync(
+    source: &ReleaseSource,
+    specifier: &UpdateRequest,
+    token: Option<&str>,
+) -> AxoupdateResult<Option<Version>> {
+    crate::block_on(latest_version(source, specifier, token))?
+}
+
+/// Looks up the release `version_specifier` selects from `source` with the
+/// built-in backend for it. This is what `AxoUpdater` does for each of its
+/// release sources, but it doesn't need one to be configured.
+pub(crate) async fn fetch_release_from(
+    client: reqwest::Client,
+    source: &ReleaseSource,
     app_name: &str,
-    release_type: &ReleaseSourceType,
-    version: &Version,
-    tokens: &AuthorizationTokens,
-) -> AxoupdateResult<Option<Release>> {
-    let release = match release_type {
+    options: &BackendOptions<'_>,
+    version_specifier: &UpdateRequest,
+    current_version: Option<&Version>,
+    skip_versions: &[Version],
+) -> AxoupdateResult<Release> {
+    let backend = builtin_backend(client, source, options)?;
+    fetch_release_for(
+        backend.as_ref(),
+        &source.name,
+        app_name,
+        version_specifier,
+        current_version,
+        skip_versions,
+    )
+    .await
+}
+
+/// How the built-in backends should look up releases, besides which
+/// release source to look them up from.
+pub(crate) struct BackendOptions<'a> {
+    pub(crate) tokens: &'a AuthorizationTokens,
+    /// Records tags which couldn't be parsed as versions
+    pub(crate) skipped_tags: SkippedTags,
+    pub(crate) allow_non_semver_tags: bool,
+    pub(crate) installer_pattern: Option<&'a str>,
+    pub(crate) prefer_api_latest: bool,
+    /// Where the GitHub backend exchanges the latest release's ETag with
+    /// the release cache, if it's enabled
+    pub(crate) latest_etag: Option<LatestEtag>,
+}
+
+/// Constructs the built-in backend for the service `source` is hosted on.
+#[cfg_attr(
+    not(all(feature = "github_releases", feature = "axo_releases")),
+    allow(unused_variables)
+)]
+pub(crate) fn builtin_backend(
+    client: reqwest::Client,
+    source: &ReleaseSource,
+    options: &BackendOptions<'_>,
+) -> AxoupdateResult<Box<dyn ReleaseBackend>> {
+    match source.release_type {
         #[cfg(feature = "github_releases")]
-        ReleaseSourceType::GitHub => {
-            github::get_specific_github_version(name, owner, app_name, version, &tokens.github)
-                .await?
-        }
+        ReleaseSourceType::GitHub => Ok(Box::new(github::GithubBackend {
+            client,
+            name: source.name.clone(),
+            owner: source.owner.clone(),
+            app_name: source.app_name.clone(),
+            token: options.tokens.github.clone(),
+            skipped_tags: options.skipped_tags.clone(),
+            allow_non_semver_tags: options.allow_non_semver_tags,
+            installer_pattern: options.installer_pattern.map(|pattern| pattern.to_owned()),
+            prefer_api_latest: options.prefer_api_latest,
+            latest_etag: options.latest_etag.clone(),
+        })),
         #[cfg(not(feature = "github_releases"))]
-        ReleaseSourceType::GitHub => {
-            return Err(AxoupdateError::BackendDisabled {
-                backend: "github".to_owned(),
-            })
-        }
+        ReleaseSourceType::GitHub => Err(AxoupdateError::BackendDisabled {
+            backend: "github".to_owned(),
+        }),
         #[cfg(feature = "axo_releases")]
-        ReleaseSourceType::Axo => {
-            axodotdev::get_specific_axo_version(name, owner, app_name, version).await?
-        }
+        ReleaseSourceType::Axo => Ok(Box::new(axodotdev::AxoBackend {
+            name: source.name.clone(),
+            owner: source.owner.clone(),
+            app_name: source.app_name.clone(),
+            token: options.tokens.axodotdev.clone(),
+            skipped_tags: options.skipped_tags.clone(),
+        })),
         #[cfg(not(feature = "axo_releases"))]
-        ReleaseSourceType::Axo => {
-            return Err(AxoupdateError::BackendDisabled {
-                backend: "axodotdev".to_owned(),
-            })
-        }
-    };
-
-    Ok(Some(release))
+        ReleaseSourceType::Axo => Err(AxoupdateError::BackendDisabled {
+            backend: "axodotdev".to_owned(),
+        }),
+    }
 }
 
-pub(crate) async fn get_specific_tag(
+/// Fetches the release matching `version_specifier` from `backend`.
+/// `name` and `app_name` are only used to describe errors. Requests which
+/// are relative to the installed version need `current_version`. Releases
+/// whose versions are in `skip_versions` are never returned.
+pub(crate) async fn fetch_release_for(
+    backend: &dyn ReleaseBackend,
     name: &str,
-    owner: &str,
     app_name: &str,
-    release_type: &ReleaseSourceType,
-    tag: &str,
-    tokens: &AuthorizationTokens,
-) -> AxoupdateResult<Option<Release>> {
-    let release = match release_type {
-        #[cfg(feature = "github_releases")]
-        ReleaseSourceType::GitHub => {
-            github::get_specific_github_tag(name, owner, app_name, tag, &tokens.github).await?
-        }
-        #[cfg(not(feature = "github_releases"))]
-        ReleaseSourceType::GitHub => {
-            return Err(AxoupdateError::BackendDisabled {
-                backend: "github".to_owned(),
-            })
+    version_specifier: &UpdateRequest,
+    current_version: Option<&Version>,
+    skip_versions: &[Version],
+) -> AxoupdateResult<Release> {
+    let not_found = |version: &str| AxoupdateError::VersionNotFound {
+        name: name.to_owned(),
+        app_name: app_name.to_owned(),
+        version: version.to_owned(),
+        status: None,
+        message: None,
+    };
+
+    let is_skipped = |release: &Release| skip_versions.contains(&release.version);
+    // The latest release may be looked up more cheaply than by listing every
+    // release; only list them if it turns out to be one we're skipping.
+    let unskipped = || async move {
+        let releases = backend.list().await?;
+        AxoupdateResult::Ok(
+            releases
+                .into_iter()
+                .filter(|r| !is_skipped(r))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let release = match version_specifier {
+        UpdateRequest::Latest => match backend.latest_stable().await? {
+            Some(release) if is_skipped(&release) => latest_stable_of(unskipped().await?),
+            release => release,
+        },
+        UpdateRequest::LatestMaybePrerelease => match backend.latest_prerelease().await? {
+            Some(release) if is_skipped(&release) => latest_of(unskipped().await?),
+            release => release,
+        },
+        UpdateRequest::Channel(channel) => backend
+            .list()
+            .await?
+            .into_iter()
+            .filter(|r| version_is_on_channel(&r.version, channel) && !is_skipped(r))
+            .max_by(compare_releases),
+        UpdateRequest::LatestWithinMajor => {
+            let Some(current_version) = current_version else {
+                return Err(AxoupdateError::NotConfigured {
+                    missing_field: "current_version".to_owned(),
+                });
+            };
+            backend
+                .list()
+                .await?
+                .into_iter()
+                .filter(|r| {
+                    !r.prerelease && r.version.major == current_version.major && !is_skipped(r)
+                })
+                .max_by(compare_releases)
         }
-        #[cfg(feature = "axo_releases")]
-        ReleaseSourceType::Axo => {
-            axodotdev::get_specific_axo_tag(name, owner, app_name, tag).await?
+        UpdateRequest::SpecificTag(tag) => {
+            let release = backend.specific_tag(tag).await?;
+            Some(release.ok_or_else(|| not_found(tag))?)
         }
-        #[cfg(not(feature = "axo_releases"))]
-        ReleaseSourceType::Axo => {
-            return Err(AxoupdateError::BackendDisabled {
-                backend: "axodotdev".to_owned(),
-            })
+        UpdateRequest::SpecificVersion(version) => {
+            let release = backend
+                .specific_version(&version.parse::<Version>()?)
+                .await?;
+            Some(release.ok_or_else(|| not_found(version))?)
         }
     };
 
-    Ok(Some(release))
+    let Some(release) = release else {
+        return Err(AxoupdateError::NoStableReleases {
+            app_name: app_name.to_owned(),
+            status: None,
+            message: None,
+        });
+    };
+    // Specifically-requested releases are refused rather than replaced
+    if is_skipped(&release) {
+        return Err(AxoupdateError::VersionSkipped {
+            version: release.version.to_string(),
+        });
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        tag = %release.tag_name,
+        version = %release.version,
+        "fetched release"
+    );
+
+    Ok(release)
 }
 
-pub(crate) async fn get_release_list(
-    name: &str,
-    owner: &str,
+/// Returns the latest stable release from `releases`.
+pub(crate) fn latest_stable_of(releases: Vec<Release>) -> Option<Release> {
+    releases
+        .into_iter()
+        .filter(|r| !r.prerelease)
+        .max_by(compare_releases)
+}
+
+/// Returns the latest release from `releases`, including prereleases.
+pub(crate) fn latest_of(releases: Vec<Release>) -> Option<Release> {
+    releases.into_iter().max_by(compare_releases)
+}
+
+/// Orders releases by version. Releases with equal versions are ordered by
+/// when they were published, and then by their tags, so that picking the
+/// latest release doesn't depend on the order the release source listed
+/// them in.
+fn compare_releases(a: &Release, b: &Release) -> Ordering {
+    a.version
+        .cmp(&b.version)
+        .then_with(|| a.published_at.cmp(&b.published_at))
+        .then_with(|| a.tag_name.cmp(&b.tag_name))
+}
+
+/// Determines whether `name` is one of `app_name`'s installers. If
+/// `installer_pattern` is set, that's any name starting with it; otherwise,
+/// it's either the generic `{app_name}-installer.*` or a platform-specific
+/// `{app_name}-{triple}-installer.*`, where `triple` is one cargo-dist
+/// builds for. This keeps another app's installers from matching when its
+/// name starts with this one's, like `foo-bar-installer.sh` for `foo`.
+pub(crate) fn is_installer_name(
     app_name: &str,
-    release_type: &ReleaseSourceType,
-    tokens: &AuthorizationTokens,
-) -> AxoupdateResult<Vec<Release>> {
-    let releases = match release_type {
-        #[cfg(feature = "github_releases")]
-        ReleaseSourceType::GitHub => {
-            github::get_github_releases(name, owner, app_name, &tokens.github).await?
-        }
-        #[cfg(not(feature = "github_releases"))]
-        ReleaseSourceType::GitHub => {
-            return Err(AxoupdateError::BackendDisabled {
-                backend: "github".to_owned(),
-            })
+    installer_pattern: Option<&str>,
+    name: &str,
+) -> bool {
+    if let Some(pattern) = installer_pattern {
+        return name.starts_with(pattern);
+    }
+
+    let Some(rest) = name
+        .strip_prefix(app_name)
+        .and_then(|rest| rest.strip_prefix('-'))
+    else {
+        return false;
+    };
+    if rest.starts_with("installer.") {
+        return true;
+    }
+
+    rest.split_once("-installer.")
+        .is_some_and(|(triple, _)| crate::platform::is_known_triple(triple))
+}
+
+/// Determines whether `name` matches the glob `pattern`, in which `*`
+/// matches any run of characters and `?` matches any single character.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // The most recent `*` and how much of the name it's matched so far, so
+    // that it can be made to match one more character if the rest fails
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
         }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Determines whether `error` means the release source itself couldn't be
+/// reached or is unavailable, in which case a fallback source may succeed.
+fn is_source_failure(error: &AxoupdateError) -> bool {
+    match error {
+        AxoupdateError::Reqwest(_)
+        | AxoupdateError::Axoasset(_)
+        | AxoupdateError::NetworkError { .. } => true,
         #[cfg(feature = "axo_releases")]
-        ReleaseSourceType::Axo => axodotdev::get_axo_releases(name, owner, app_name).await?,
-        #[cfg(not(feature = "axo_releases"))]
-        ReleaseSourceType::Axo => {
-            return Err(AxoupdateError::BackendDisabled {
-                backend: "axodotdev".to_owned(),
-            })
+        AxoupdateError::Gazenot(_) => true,
+        AxoupdateError::NoStableReleases {
+            status: Some(status),
+            ..
         }
-    };
-    Ok(releases)
+        | AxoupdateError::VersionNotFound {
+            status: Some(status),
+            ..
+        } => *status == 403 || *status == 429 || *status >= 500,
+        _ => false,
+    }
 }
 
-/// Get the latest stable release
-pub(crate) async fn get_latest_stable_release(
-    name: &str,
-    owner: &str,
-    app_name: &str,
-    release_type: &ReleaseSourceType,
-    tokens: &AuthorizationTokens,
-) -> AxoupdateResult<Option<Release>> {
-    // GitHub has an API to request the latest stable release.
-    // If we're looking up a GitHub release, we can use that.
-    // This cuts down on our API requests compared to the paginated release list
-    // we do below.
-    // Note that abyss has an API for this, but gazenot doesn't expose it yet;
-    // we can expand this pattern to Axo Releases in a later release.
-    // It's less critical for that path because the rate limits are less of a
-    // blocker.
+/// Determines whether `release` would be considered by `version_specifier`
+/// when looking for the latest release.
+pub(crate) fn release_matches_request(
+    release: &Release,
+    version_specifier: &UpdateRequest,
+) -> bool {
+    match version_specifier {
+        UpdateRequest::LatestMaybePrerelease => true,
+        UpdateRequest::Channel(channel) => version_is_on_channel(&release.version, channel),
+        UpdateRequest::Latest
+        | UpdateRequest::LatestWithinMajor
+        | UpdateRequest::SpecificVersion(_)
+        | UpdateRequest::SpecificTag(_) => !release.prerelease,
+    }
+}
+
+/// Determines whether `version` belongs to the release channel `channel`,
+/// as reported by `channel_of`. The special channel `stable` matches
+/// versions with no prerelease component.
+fn version_is_on_channel(version: &Version, channel: &str) -> bool {
+    if channel == "stable" {
+        version.pre.is_empty()
+    } else {
+        channel_of(version).as_deref() == Some(channel)
+    }
+}
+
+/// Returns the release channel `version` belongs to, if it's a prerelease.
+/// This is the leading, non-numeric part of its prerelease component; for
+/// example, `beta` for `1.2.0-beta.1` or `rc` for `1.2.0-rc1`.
+pub(crate) fn channel_of(version: &Version) -> Option<String> {
+    let channel: String = version
+        .pre
+        .as_str()
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic() || *c == '-')
+        .collect();
+    let channel = channel.trim_end_matches('-');
+
+    if channel.is_empty() {
+        None
+    } else {
+        Some(channel.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        channel_of, fetch_release_for, glob_matches, is_installer_name, latest_of,
+        version_is_on_channel, Asset,
+    };
+    use crate::test::fixtures::{asset, release, release_with_assets, StaticBackend};
+    use crate::{AxoupdateError, UpdateRequest, Version};
     #[cfg(feature = "github_releases")]
-    if release_type == &ReleaseSourceType::GitHub {
-        if let Ok(Some(release)) =
-            github::get_latest_github_release(name, owner, app_name, &tokens.github).await
-        {
-            return Ok(Some(release));
+    use {
+        super::{latest_version, ReleaseSource, ReleaseSourceType},
+        crate::test::fixtures::github_release_json,
+        crate::AxoUpdater,
+        axoasset::serde_json::json,
+        httpmock::prelude::*,
+        serial_test::serial,
+    };
+
+    #[test]
+    fn test_latest_is_deterministic() {
+        let a = release("1.0.0+a");
+        let b = release("1.0.0+b");
+        let older = release("0.9.0");
+
+        let forwards = latest_of(vec![a.clone(), b.clone(), older.clone()]).unwrap();
+        let backwards = latest_of(vec![older, b, a]).unwrap();
+        assert_eq!(forwards.tag_name, backwards.tag_name);
+        assert_eq!(forwards.tag_name, "v1.0.0+b");
+
+        // Identical versions published under different tags
+        let mut plain = release("1.0.0");
+        plain.tag_name = "1.0.0".to_owned();
+        let prefixed = release("1.0.0");
+        let forwards = latest_of(vec![plain.clone(), prefixed.clone()]).unwrap();
+        let backwards = latest_of(vec![prefixed, plain]).unwrap();
+        assert_eq!(forwards.tag_name, backwards.tag_name);
+
+        // When available, the publish date takes precedence over the tag
+        let mut earlier = release("1.0.0");
+        earlier.tag_name = "z1.0.0".to_owned();
+        earlier.published_at = Some("2024-01-01T00:00:00Z".to_owned());
+        let mut later = release("1.0.0");
+        later.published_at = Some("2024-02-01T00:00:00Z".to_owned());
+        assert_eq!(
+            latest_of(vec![later.clone(), earlier])
+                .unwrap()
+                .published_at,
+            later.published_at
+        );
+    }
+
+    #[test]
+    fn test_is_installer_name() {
+        assert!(is_installer_name(
+            "axolotlsay",
+            None,
+            "axolotlsay-installer.sh"
+        ));
+        assert!(is_installer_name(
+            "axolotlsay",
+            None,
+            "axolotlsay-installer.ps1"
+        ));
+        assert!(is_installer_name(
+            "axolotlsay",
+            None,
+            "axolotlsay-x86_64-unknown-linux-gnu-installer.sh"
+        ));
+        assert!(!is_installer_name(
+            "axolotlsay",
+            None,
+            "axolotlsay-x86_64-unknown-linux-gnu.tar.gz"
+        ));
+        assert!(!is_installer_name("axolotlsay", None, "other-installer.sh"));
+        // Another app whose name starts with this one's
+        assert!(!is_installer_name(
+            "axolotlsay",
+            None,
+            "axolotlsay-extras-installer.sh"
+        ));
+        assert!(!is_installer_name(
+            "axolotlsay",
+            None,
+            "axolotlsay-installerish.sh"
+        ));
+
+        // A custom pattern replaces the default naming scheme
+        assert!(is_installer_name(
+            "axolotlsay",
+            Some("axolotlsay-setup"),
+            "axolotlsay-setup.sh"
+        ));
+        assert!(!is_installer_name(
+            "axolotlsay",
+            Some("axolotlsay-setup"),
+            "axolotlsay-installer.sh"
+        ));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*.sig", "app-installer.sh.sig"));
+        assert!(!glob_matches("*.sig", "app-installer.sh"));
+        assert!(glob_matches("completions-*.zip", "completions-bash.zip"));
+        assert!(!glob_matches(
+            "completions-*.zip",
+            "completions-bash.tar.gz"
+        ));
+        assert!(glob_matches("app-?.zip", "app-1.zip"));
+        assert!(!glob_matches("app-?.zip", "app-10.zip"));
+        assert!(glob_matches("*-*-installer.sh", "app-x86_64-installer.sh"));
+        assert!(glob_matches("a*b*c", "abcbc"));
+        assert!(!glob_matches("a*b*c", "abcb"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("exact", "exactly"));
+    }
+
+    #[test]
+    fn test_find_assets() {
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                asset("app-installer.sh"),
+                asset("app-installer.sh.sig"),
+                asset("app-x86_64-apple-darwin.tar.gz"),
+                asset("app-x86_64-apple-darwin.tar.gz.sig"),
+                asset("completions-bash.zip"),
+            ],
+        );
+        let names = |assets: Vec<&Asset>| {
+            assets
+                .into_iter()
+                .map(|asset| asset.name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(release.find_assets("*.sig")),
+            ["app-installer.sh.sig", "app-x86_64-apple-darwin.tar.gz.sig"]
+        );
+        assert_eq!(
+            names(release.find_assets("completions-*.zip")),
+            ["completions-bash.zip"]
+        );
+        assert!(release.find_assets("*.msi").is_empty());
+        assert_eq!(
+            release
+                .find_asset("app-installer.sh")
+                .map(|a| a.name.as_str()),
+            Some("app-installer.sh")
+        );
+
+        #[cfg(feature = "regex")]
+        assert_eq!(
+            names(release.find_assets_regex(&regex::Regex::new(r"^app-.*\.tar\.gz$").unwrap())),
+            ["app-x86_64-apple-darwin.tar.gz"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_backend() {
+        let backend = &StaticBackend(vec![
+            release("1.0.0"),
+            release("1.1.0"),
+            release("1.2.0-beta.1"),
+        ]);
+        let fetch = |request: UpdateRequest| async move {
+            fetch_release_for(backend, "name", "app", &request, None, &[]).await
+        };
+
+        let latest = fetch(UpdateRequest::Latest).await.unwrap();
+        assert_eq!(latest.version.to_string(), "1.1.0");
+
+        let prerelease = fetch(UpdateRequest::LatestMaybePrerelease).await.unwrap();
+        assert_eq!(prerelease.version.to_string(), "1.2.0-beta.1");
+
+        let tagged = fetch(UpdateRequest::SpecificTag("v1.0.0".to_owned()))
+            .await
+            .unwrap();
+        assert_eq!(tagged.version.to_string(), "1.0.0");
+
+        let missing = fetch(UpdateRequest::SpecificVersion("2.0.0".to_owned())).await;
+        assert!(matches!(
+            missing,
+            Err(AxoupdateError::VersionNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_skip_versions() {
+        let backend = &StaticBackend(vec![
+            release("1.0.0"),
+            release("1.1.0"),
+            release("1.2.0-beta.1"),
+        ]);
+        let skip = &[
+            Version::parse("1.1.0").unwrap(),
+            Version::parse("1.2.0-beta.1").unwrap(),
+        ];
+        let fetch = |request: UpdateRequest| async move {
+            fetch_release_for(backend, "name", "app", &request, None, skip).await
+        };
+
+        let latest = fetch(UpdateRequest::Latest).await.unwrap();
+        assert_eq!(latest.version.to_string(), "1.0.0");
+
+        let prerelease = fetch(UpdateRequest::LatestMaybePrerelease).await.unwrap();
+        assert_eq!(prerelease.version.to_string(), "1.0.0");
+
+        assert!(matches!(
+            fetch(UpdateRequest::SpecificVersion("1.1.0".to_owned())).await,
+            Err(AxoupdateError::VersionSkipped { .. })
+        ));
+        assert!(matches!(
+            fetch(UpdateRequest::SpecificTag("v1.2.0-beta.1".to_owned())).await,
+            Err(AxoupdateError::VersionSkipped { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_latest_within_major() {
+        let backend = &StaticBackend(vec![
+            release("1.0.0"),
+            release("1.4.2"),
+            release("1.5.0-beta.1"),
+            release("2.0.0"),
+        ]);
+        let fetch = |current: Option<&'static str>| async move {
+            let current = current.map(|v| Version::parse(v).unwrap());
+            fetch_release_for(
+                backend,
+                "name",
+                "app",
+                &UpdateRequest::LatestWithinMajor,
+                current.as_ref(),
+                &[],
+            )
+            .await
+        };
+
+        let release = fetch(Some("1.0.0")).await.unwrap();
+        assert_eq!(release.version.to_string(), "1.4.2");
+
+        let release = fetch(Some("2.0.0")).await.unwrap();
+        assert_eq!(release.version.to_string(), "2.0.0");
+
+        assert!(matches!(
+            fetch(Some("3.0.0")).await,
+            Err(AxoupdateError::NoStableReleases { .. })
+        ));
+        assert!(matches!(
+            fetch(None).await,
+            Err(AxoupdateError::NotConfigured { .. })
+        ));
+    }
+
+    #[test]
+    fn test_version_channels() {
+        let stable = Version::parse("1.2.0").unwrap();
+        let beta = Version::parse("1.2.0-beta.1").unwrap();
+        let nightly = Version::parse("1.2.0-nightly.20240101").unwrap();
+
+        assert!(version_is_on_channel(&stable, "stable"));
+        assert!(!version_is_on_channel(&beta, "stable"));
+        assert!(version_is_on_channel(&beta, "beta"));
+        assert!(!version_is_on_channel(&nightly, "beta"));
+        assert!(version_is_on_channel(&nightly, "nightly"));
+        assert!(!version_is_on_channel(&stable, "nightly"));
+        // Channels are compared whole, not as prefixes
+        assert!(!version_is_on_channel(&nightly, "night"));
+        assert!(!version_is_on_channel(
+            &Version::parse("1.2.0-betamax.1").unwrap(),
+            "beta"
+        ));
+
+        assert_eq!(channel_of(&stable), None);
+        assert_eq!(channel_of(&beta).as_deref(), Some("beta"));
+        assert_eq!(channel_of(&nightly).as_deref(), Some("nightly"));
+        assert_eq!(
+            channel_of(&Version::parse("1.2.0-rc1").unwrap()).as_deref(),
+            Some("rc")
+        );
+        assert_eq!(channel_of(&Version::parse("1.2.0-1").unwrap()), None);
+    }
+
+    #[cfg(feature = "github_releases")]
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_fallback_sources() {
+        let server = MockServer::start_async().await;
+        std::env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
+
+        let source = |name: &str| ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "owner".to_owned(),
+            name: name.to_owned(),
+            app_name: "app".to_owned(),
+        };
+        let down = server
+            .mock_async(|when, then| {
+                when.method(GET).path_contains("/repos/owner/down/");
+                then.status(503);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/mirror/releases/latest");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(github_release_json("v1.2.0", "app-installer.sh"));
+            })
+            .await;
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater
+            .set_release_source(source("down"))
+            .set_fallback_sources(vec![source("down"), source("mirror")]);
+        let release = updater.peek_latest_release().await;
+
+        updater.set_fallback_sources(vec![source("down")]);
+        let failed = updater.peek_latest_release().await;
+        std::env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        assert_eq!(release.unwrap().version, Version::parse("1.2.0").unwrap());
+        assert!(down.hits_async().await > 0);
+        match failed {
+            Err(AxoupdateError::AllSourcesFailed { errors }) => assert_eq!(errors.len(), 2),
+            other => panic!("expected AllSourcesFailed, got {other:?}"),
         }
     }
 
-    let releases = get_release_list(name, owner, app_name, release_type, tokens).await?;
-    Ok(releases
-        .into_iter()
-        .filter(|r| !r.prerelease)
-        .max_by_key(|r| r.version.clone()))
-}
+    #[cfg(feature = "github_releases")]
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_validate_source() {
+        let server = MockServer::start_async().await;
+        std::env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
 
-/// Get the latest release, allowing for prereleases
-pub(crate) async fn get_latest_maybe_prerelease(
-    name: &str,
-    owner: &str,
-    app_name: &str,
-    release_type: &ReleaseSourceType,
-    tokens: &AuthorizationTokens,
-) -> AxoupdateResult<Option<Release>> {
-    let releases = get_release_list(name, owner, app_name, release_type, tokens).await?;
-    Ok(releases.into_iter().max_by_key(|r| r.version.clone()))
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/missing");
+                then.status(404)
+                    .header("content-type", "application/json")
+                    .json_body(json!({"message": "Not Found"}));
+            })
+            .await;
+        for name in ["good", "other"] {
+            server
+                .mock_async(|when, then| {
+                    when.method(GET).path(format!("/repos/owner/{name}"));
+                    then.status(200)
+                        .header("content-type", "application/json")
+                        .json_body(json!({ "name": name }));
+                })
+                .await;
+        }
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/good/releases");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([github_release_json("v1.0.0", "app-installer.sh")]));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/other/releases");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([github_release_json("v1.0.0", "other-installer.sh")]));
+            })
+            .await;
+
+        let validate = |name: &str| {
+            let mut updater = AxoUpdater::new_for("app");
+            updater.set_release_source(ReleaseSource {
+                release_type: ReleaseSourceType::GitHub,
+                owner: "owner".to_owned(),
+                name: name.to_owned(),
+                app_name: "app".to_owned(),
+            });
+            async move { updater.validate_source().await }
+        };
+        let good = validate("good").await;
+        let missing = validate("missing").await;
+        let other = validate("other").await;
+        let unconfigured = AxoUpdater::new_for("app").validate_source().await;
+        std::env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        assert!(good.is_ok());
+        assert!(matches!(
+            missing,
+            Err(AxoupdateError::ReleaseSourceNotFound {
+                status: Some(404),
+                ..
+            })
+        ));
+        assert!(matches!(
+            other,
+            Err(AxoupdateError::NoInstallersFound { .. })
+        ));
+        assert!(matches!(
+            unconfigured,
+            Err(AxoupdateError::NotConfigured { .. })
+        ));
+    }
+
+    #[cfg(feature = "github_releases")]
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_prefer_api_latest() {
+        let server = MockServer::start_async().await;
+        std::env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
+
+        let release = |tag: &str| github_release_json(tag, "app-installer.sh");
+        // An older release has been marked as the latest one
+        let latest = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/name/releases/latest");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(release("v1.0.0"));
+            })
+            .await;
+        let list = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/name/releases");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([release("v1.0.0"), release("v1.1.0")]));
+            })
+            .await;
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater.set_release_source(ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "owner".to_owned(),
+            name: "name".to_owned(),
+            app_name: "app".to_owned(),
+        });
+        let api_latest = updater.peek_latest_release().await;
+
+        updater.set_prefer_api_latest(false);
+        let semver_latest = updater.peek_latest_release().await;
+        std::env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        assert_eq!(
+            api_latest.unwrap().version,
+            Version::parse("1.0.0").unwrap()
+        );
+        assert_eq!(
+            semver_latest.unwrap().version,
+            Version::parse("1.1.0").unwrap()
+        );
+        latest.assert_hits_async(1).await;
+        list.assert_hits_async(1).await;
+    }
+
+    #[cfg(feature = "github_releases")]
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_latest_version() {
+        let server = MockServer::start_async().await;
+        std::env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
+
+        let latest = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/owner/name/releases/latest")
+                    .header("authorization", "Bearer token");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(github_release_json("v1.2.0", "app-installer.sh"));
+            })
+            .await;
+        let empty = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/owner/empty/releases");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([]));
+            })
+            .await;
+
+        let source = |name: &str| ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "owner".to_owned(),
+            name: name.to_owned(),
+            app_name: "app".to_owned(),
+        };
+        let found = latest_version(&source("name"), &UpdateRequest::Latest, Some("token")).await;
+        // A source without any releases has no latest version
+        let missing = latest_version(&source("empty"), &UpdateRequest::Latest, None).await;
+        std::env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        assert_eq!(found.unwrap(), Some(Version::parse("1.2.0").unwrap()));
+        assert_eq!(missing.unwrap(), None);
+        latest.assert_hits_async(1).await;
+        empty.assert_hits_async(1).await;
+    }
 }
@@ -0,0 +1,157 @@
+//! Updating the standalone updater itself
+
+use std::env::current_exe;
+
+use camino::Utf8PathBuf;
+use tempfile::TempDir;
+
+use crate::{
+    archive::{find_file, replace_binary},
+    errors::*,
+    release::{fetch_release_from, BackendOptions},
+    AxoUpdater, NoUpdateReason, ReleaseSource, SkippedTags, UpdateOutcome, UpdateRequest,
+    UpdateResult, Version,
+};
+
+/// The name of the binary in the standalone updater's archives
+const UPDATER_BINARY: &str = "axoupdater";
+
+impl AxoUpdater {
+    /// Updates the running standalone updater, such as an `{app}-update`
+    /// binary installed alongside an app, to the latest release from
+    /// `source`. `current_version` is the updater's own version, and
+    /// `source` is where the updater is released from rather than where
+    /// the app is; for axoupdater itself, that's the `axoupdater-cli` app
+    /// in the `axodotdev/axoupdater` repository on GitHub.
+    ///
+    /// The new updater is downloaded from the release's archive for this
+    /// platform, so this requires the `archive_install` feature. The
+    /// running executable is replaced without ever being partially
+    /// written: on Unix, the new binary is moved over it atomically, and on
+    /// Windows, the running executable is first moved aside.
+    ///
+    /// This uses this updater's HTTP client configuration and authorization
+    /// tokens, but is otherwise independent of the app it's configured for.
+    pub async fn update_self_updater(
+        &self,
+        source: ReleaseSource,
+        current_version: Version,
+    ) -> AxoupdateResult<UpdateOutcome> {
+        let options = BackendOptions {
+            tokens: &self.tokens,
+            skipped_tags: SkippedTags::default(),
+            allow_non_semver_tags: false,
+            installer_pattern: None,
+            prefer_api_latest: true,
+            latest_etag: None,
+        };
+        let release = fetch_release_from(
+            self.http_client()?,
+            &source,
+            &source.app_name,
+            &options,
+            &UpdateRequest::Latest,
+            Some(&current_version),
+            &[],
+        )
+        .await?;
+        if release.version <= current_version {
+            return Ok(UpdateOutcome::Skipped(NoUpdateReason::UpToDate));
+        }
+
+        let Some(archive) = self.select_archive_asset(&release, &source.app_name) else {
+            return Err(AxoupdateError::NoInstallerForPackage {});
+        };
+
+        let tempdir = TempDir::new()?;
+        let extracted = self.download_archive(&release, archive, &tempdir).await?;
+        let binary = format!("{UPDATER_BINARY}{}", std::env::consts::EXE_SUFFIX);
+        let Some(new_updater) = find_file(&extracted, &binary)? else {
+            return Err(AxoupdateError::BinaryNotInArchive { binary });
+        };
+
+        let ourselves = Utf8PathBuf::from_path_buf(current_exe()?.canonicalize()?)
+            .map_err(|path| AxoupdateError::CaminoConversionFailed { path })?;
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            path = %ourselves,
+            version = %release.version,
+            "replacing standalone updater"
+        );
+
+        replace_binary(&new_updater, &ourselves, true)?;
+
+        Ok(UpdateOutcome::Updated(UpdateResult::for_release(
+            Some(current_version),
+            &release,
+            ourselves
+                .parent()
+                .map(|parent| parent.to_owned())
+                .unwrap_or_default(),
+        )))
+    }
+}
+
+#[cfg(all(test, feature = "github_releases"))]
+mod test {
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    use crate::test::fixtures::github_release_json;
+    use crate::{
+        AxoUpdater, AxoupdateError, NoUpdateReason, ReleaseSource, ReleaseSourceType,
+        UpdateOutcome, Version,
+    };
+
+    #[tokio::test]
+    #[serial] // modifying the global state environment variables
+    async fn test_update_self_updater() {
+        let server = MockServer::start_async().await;
+        std::env::set_var("AXOUPDATER_GITHUB_API_BASE", server.base_url());
+        let latest = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/axodotdev/axoupdater/releases/latest");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(github_release_json("v1.0.0", "axoupdater-cli-installer.sh"));
+            })
+            .await;
+
+        let source = ReleaseSource {
+            release_type: ReleaseSourceType::GitHub,
+            owner: "axodotdev".to_owned(),
+            name: "axoupdater".to_owned(),
+            app_name: "axoupdater-cli".to_owned(),
+        };
+        let version = |version: &str| Version::parse(version).unwrap();
+        // The updater is released separately from the app it's installed for
+        let updater = AxoUpdater::new_for("app");
+        let same = updater
+            .update_self_updater(source.clone(), version("1.0.0"))
+            .await;
+        let newer = updater
+            .update_self_updater(source.clone(), version("1.1.0"))
+            .await;
+        let older = updater.update_self_updater(source, version("0.9.0")).await;
+        std::env::remove_var("AXOUPDATER_GITHUB_API_BASE");
+
+        latest.assert_hits_async(3).await;
+        assert!(matches!(
+            same,
+            Ok(UpdateOutcome::Skipped(NoUpdateReason::UpToDate))
+        ));
+        // Updaters newer than the latest release are never downgraded
+        assert!(matches!(
+            newer,
+            Ok(UpdateOutcome::Skipped(NoUpdateReason::UpToDate))
+        ));
+        // Older ones are updated from the release's archive, which this
+        // release doesn't have
+        assert!(matches!(
+            older,
+            Err(AxoupdateError::NoInstallerForPackage {})
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+//! Choosing where to stage downloaded installers before running them
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::{errors::*, AxoUpdater};
+
+/// Why a directory was chosen to stage the installer in
+#[derive(Clone, Debug, PartialEq)]
+pub enum StagingReason {
+    /// It was set with `AxoUpdater::set_staging_dir_override`
+    Override,
+    /// It's on the same volume as the install prefix
+    SameVolumeAsPrefix,
+    /// It was the first of the usual runtime, cache and temporary
+    /// directories that could be used
+    Default,
+}
+
+/// The directory an installer was staged in, and why it was chosen
+#[derive(Clone, Debug, PartialEq)]
+pub struct StagingChoice {
+    /// The directory the installer's temporary directory was created in
+    pub root: PathBuf,
+    /// Why this directory was chosen
+    pub reason: StagingReason,
+    /// Whether a test script was successfully run from the directory. This
+    /// is only checked on Unix; PowerShell installers don't need it.
+    pub exec_probe_passed: bool,
+    /// The directories which were tried before this one, with why each one
+    /// couldn't be used
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+impl AxoUpdater {
+    /// Stages downloaded installers in `dir` instead of picking a directory
+    /// automatically. This is useful on systems where the usual runtime,
+    /// cache and temporary directories are all mounted `noexec`.
+    ///
+    /// The directory is still checked before use, and the update fails if
+    /// files in it can't be executed.
+    pub fn set_staging_dir_override(&mut self, dir: PathBuf) -> &mut AxoUpdater {
+        self.staging_dir_override = Some(dir);
+
+        self
+    }
+
+    /// Returns where the installer was staged during the last update, and
+    /// why that directory was chosen. This can help diagnose installers
+    /// which fail because they couldn't be executed.
+    pub fn last_staging_choice(&self) -> Option<StagingChoice> {
+        self.last_staging_choice
+            .lock()
+            .ok()
+            .and_then(|choice| choice.clone())
+    }
+
+    /// Creates a temporary directory that the installer can be run from.
+    ///
+    /// The override from `set_staging_dir_override` is used if set;
+    /// otherwise the user's runtime directory, cache directory and the
+    /// system temporary directory are tried in turn. Each candidate is
+    /// probed by running a small script from it, and the first one that
+    /// works is used.
+    ///
+    /// If `install_prefix` is known, candidates on the same volume as it are
+    /// tried first. Some installers copy and then delete the files they
+    /// install, which is slow or fails outright across volumes. The prefix
+    /// itself is never used, so that staging never leaves files in it.
+    pub(crate) fn select_installer_tempdir(
+        &self,
+        install_prefix: Option<&Path>,
+    ) -> AxoupdateResult<TempDir> {
+        let candidates = if let Some(dir) = &self.staging_dir_override {
+            vec![dir.to_owned()]
+        } else if let Some(install_prefix) = install_prefix {
+            prefer_same_volume(staging_dir_candidates(), install_prefix)
+        } else {
+            staging_dir_candidates()
+        };
+        let prefix_volume = install_prefix.and_then(volume_of);
+
+        let mut rejected = vec![];
+        for candidate in candidates {
+            match TempDir::new_in(&candidate)
+                .map_err(|e| e.to_string())
+                .and_then(|tempdir| can_execute_from_dir(&tempdir).map(|_| tempdir))
+            {
+                Ok(tempdir) => {
+                    let reason = if self.staging_dir_override.is_some() {
+                        StagingReason::Override
+                    } else if prefix_volume.is_some() && volume_of(&candidate) == prefix_volume {
+                        StagingReason::SameVolumeAsPrefix
+                    } else {
+                        StagingReason::Default
+                    };
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(dir = %candidate.display(), ?reason, "staging installer");
+                    let choice = StagingChoice {
+                        root: candidate,
+                        reason,
+                        exec_probe_passed: cfg!(unix),
+                        rejected,
+                    };
+                    if let Ok(mut last) = self.last_staging_choice.lock() {
+                        *last = Some(choice);
+                    }
+
+                    return Ok(tempdir);
+                }
+                Err(reason) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(dir = %candidate.display(), %reason, "can't stage installer here");
+                    rejected.push((candidate, reason));
+                }
+            }
+        }
+
+        let tried = rejected
+            .iter()
+            .map(|(dir, reason)| format!("  {}: {reason}", dir.display()))
+            .collect::<Vec<_>>();
+        Err(AxoupdateError::NoExecutableStagingDir {
+            tried: tried.join("\n"),
+        })
+    }
+}
+
+/// The directories to try staging installers in, in order of preference.
+fn staging_dir_candidates() -> Vec<PathBuf> {
+    let mut candidates = vec![];
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir));
+        }
+        if let Some(cache_dir) = std::env::var_os("XDG_CACHE_HOME") {
+            candidates.push(PathBuf::from(cache_dir));
+        } else if let Ok(Some(home)) = homedir::my_home() {
+            candidates.push(home.join(".cache"));
+        }
+    }
+
+    candidates.push(std::env::temp_dir());
+
+    candidates
+}
+
+/// Reorders `candidates` so that those on the same volume as
+/// `install_prefix` come first, keeping the order otherwise. Nothing is
+/// added or removed.
+///
+/// Only paths with a volume prefix, like `C:\` on Windows, are affected;
+/// everything else is left in order.
+fn prefer_same_volume(candidates: Vec<PathBuf>, install_prefix: &Path) -> Vec<PathBuf> {
+    let Some(volume) = volume_of(install_prefix) else {
+        return candidates;
+    };
+
+    let (mut same, other): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|candidate| volume_of(candidate).as_ref() == Some(&volume));
+    same.extend(other);
+
+    same
+}
+
+/// The volume `path` is on, normalized so that `c:` and `C:` compare equal.
+fn volume_of(path: &Path) -> Option<OsString> {
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) => Some(prefix.as_os_str().to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+/// Checks that files in `dir` can be executed by writing and running a
+/// trivial script. Returns why not if they can't.
+#[cfg(unix)]
+fn can_execute_from_dir(dir: &TempDir) -> Result<(), String> {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    let probe = dir.path().join("probe.sh");
+    fs::write(&probe, "#!/bin/sh\nexit 0\n").map_err(|e| format!("couldn't write a file: {e}"))?;
+    fs::set_permissions(&probe, fs::Permissions::from_mode(0o744))
+        .map_err(|e| format!("couldn't mark a file executable: {e}"))?;
+
+    match Command::new(&probe).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("a test script exited with {status}")),
+        Err(e) => Err(format!("couldn't run a test script: {e}")),
+    }
+}
+
+/// PowerShell installers are run through the interpreter rather than
+/// executed directly, so any writable directory will do.
+#[cfg(not(unix))]
+fn can_execute_from_dir(_dir: &TempDir) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::prefer_same_volume;
+    use crate::{AxoUpdater, AxoupdateError, StagingReason};
+
+    #[test]
+    fn test_staging_dir_override() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater.set_staging_dir_override(dir.path().to_path_buf());
+        assert!(updater.last_staging_choice().is_none());
+        let staged = updater.select_installer_tempdir(None).unwrap();
+        assert!(staged.path().starts_with(dir.path()));
+
+        let choice = updater.last_staging_choice().unwrap();
+        assert_eq!(choice.root, dir.path());
+        assert_eq!(choice.reason, StagingReason::Override);
+        assert!(choice.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_staging_dir_override_missing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let missing = dir.path().join("missing");
+
+        let mut updater = AxoUpdater::new_for("app");
+        updater.set_staging_dir_override(missing.clone());
+        let Err(AxoupdateError::NoExecutableStagingDir { tried }) =
+            updater.select_installer_tempdir(None)
+        else {
+            panic!("expected NoExecutableStagingDir");
+        };
+        assert!(tried.contains(&missing.display().to_string()));
+    }
+
+    #[test]
+    fn test_prefer_same_volume_without_volumes() {
+        let candidates = vec![PathBuf::from("/run/user/1000"), PathBuf::from("/tmp")];
+        let ordered = prefer_same_volume(candidates.clone(), Path::new("/home/axo/.cargo/bin"));
+        assert_eq!(ordered, candidates);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_prefer_same_volume() {
+        let candidates = vec![
+            PathBuf::from(r"C:\Users\axo\AppData\Local\Temp"),
+            PathBuf::from(r"d:\cache"),
+        ];
+
+        let ordered = prefer_same_volume(candidates.clone(), Path::new(r"D:\tools\bin"));
+        assert_eq!(
+            ordered,
+            vec![
+                PathBuf::from(r"d:\cache"),
+                PathBuf::from(r"C:\Users\axo\AppData\Local\Temp"),
+            ]
+        );
+
+        // The order is unchanged when no candidate shares the prefix's volume
+        let ordered = prefer_same_volume(candidates.clone(), Path::new(r"E:\tools\bin"));
+        assert_eq!(ordered, candidates);
+    }
+}
@@ -0,0 +1,215 @@
+//! Summarizing the difference between the installed and available versions
+
+use std::cmp::Ordering;
+
+use crate::{errors::*, latest_stable_of, release_matches_request, AxoUpdater, Release, Version};
+
+/// Whether installing the target release would be an upgrade
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpdateDirection {
+    /// The target release is newer than the installed version
+    Upgrade,
+    /// The target release is older than the installed version
+    Downgrade,
+    /// The target release is the installed version
+    Same,
+}
+
+/// A comparison of the installed version with the release that would be
+/// installed, for displaying "what's new" information
+#[derive(Clone, Debug)]
+pub struct UpdateSummary {
+    /// The installed version
+    pub current_version: Version,
+    /// The release that would be installed
+    pub target: Release,
+    /// Whether installing the target would be an upgrade
+    pub direction: UpdateDirection,
+    /// The releases between the installed version and the target, oldest
+    /// first, not including either of them. Only releases which the
+    /// configured version request would consider are included; for example,
+    /// prereleases are left out unless prereleases were requested.
+    pub intervening_releases: Vec<Release>,
+}
+
+impl AxoUpdater {
+    /// Compares the installed version with the release that would be
+    /// installed, and lists the releases between them. This requires the
+    /// current version to be known, either from the install receipt or
+    /// `set_current_version`.
+    ///
+    /// Listing the intervening releases may require fetching the full
+    /// release list; it's skipped when the target is the installed version.
+    pub async fn update_summary(&mut self) -> AxoupdateResult<UpdateSummary> {
+        let current_version = self.required_current_version()?;
+
+        if self.requested_release.is_none() {
+            self.fetch_release().await?;
+        }
+        let target =
+            self.requested_release
+                .clone()
+                .ok_or_else(|| AxoupdateError::NotConfigured {
+                    missing_field: "requested_release".to_owned(),
+                })?;
+
+        let direction = match target.version.cmp(&current_version) {
+            Ordering::Greater => UpdateDirection::Upgrade,
+            Ordering::Less => UpdateDirection::Downgrade,
+            Ordering::Equal => UpdateDirection::Same,
+        };
+
+        let intervening_releases = if direction == UpdateDirection::Same {
+            vec![]
+        } else {
+            let (low, high) = if direction == UpdateDirection::Upgrade {
+                (&current_version, &target.version)
+            } else {
+                (&target.version, &current_version)
+            };
+            let mut releases: Vec<Release> = self
+                .list_releases()
+                .await?
+                .into_iter()
+                .filter(|r| &r.version > low && &r.version < high)
+                .filter(|r| release_matches_request(r, &self.version_specifier))
+                .collect();
+            releases.sort_by(|a, b| a.version.cmp(&b.version));
+            releases
+        };
+
+        Ok(UpdateSummary {
+            current_version,
+            target,
+            direction,
+            intervening_releases,
+        })
+    }
+
+    /// Counts the stable releases which are newer than the installed
+    /// version. This requires the current version to be known, and fetches
+    /// the full release list.
+    pub async fn releases_behind(&self) -> AxoupdateResult<usize> {
+        let current_version = self.required_current_version()?;
+
+        Ok(self
+            .list_releases()
+            .await?
+            .iter()
+            .filter(|r| !r.prerelease && r.version > current_version)
+            .count())
+    }
+
+    /// Summarizes how far the installed version is behind the latest stable
+    /// release as a `(major, minor, patch)` gap, for messages like "2 minor
+    /// versions behind". Each component only counts releases since the last
+    /// bump of the component above it: going from `1.2.3` to `2.1.0` is
+    /// `(1, 1, 0)`, and from `1.2.3` to `1.4.1` is `(0, 2, 1)`. If the
+    /// installed version is up to date or newer, this is `(0, 0, 0)`.
+    pub async fn versions_behind_semver(&self) -> AxoupdateResult<(u64, u64, u64)> {
+        let current = self.required_current_version()?;
+        let Some(latest) = latest_stable_of(self.list_releases().await?) else {
+            return Ok((0, 0, 0));
+        };
+        let latest = latest.version;
+
+        if latest <= current {
+            return Ok((0, 0, 0));
+        }
+        if latest.major != current.major {
+            return Ok((latest.major - current.major, latest.minor, latest.patch));
+        }
+        if latest.minor != current.minor {
+            return Ok((0, latest.minor - current.minor, latest.patch));
+        }
+        Ok((0, 0, latest.patch.saturating_sub(current.patch)))
+    }
+
+    fn required_current_version(&self) -> AxoupdateResult<Version> {
+        self.current_version
+            .clone()
+            .ok_or_else(|| AxoupdateError::NotConfigured {
+                missing_field: "current_version".to_owned(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UpdateDirection;
+    use crate::test::fixtures::{release, StaticBackend};
+    use crate::{AxoUpdater, Release, UpdateRequest, Version};
+
+    fn updater(current: &str, request: UpdateRequest) -> AxoUpdater {
+        let mut updater = AxoUpdater::new_for("app");
+        updater
+            .set_custom_backend(Box::new(StaticBackend(vec![
+                release("1.3.0"),
+                release("1.0.0"),
+                release("1.1.0"),
+                release("1.2.0-beta.1"),
+                release("1.2.0"),
+            ])))
+            .configure_version_specifier(request)
+            .set_current_version(Version::parse(current).unwrap())
+            .unwrap();
+        updater
+    }
+
+    #[tokio::test]
+    async fn test_update_summary() {
+        let versions = |releases: &[Release]| {
+            releases
+                .iter()
+                .map(|r| r.version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let summary = updater("1.0.0", UpdateRequest::Latest)
+            .update_summary()
+            .await
+            .unwrap();
+        assert_eq!(summary.direction, UpdateDirection::Upgrade);
+        assert_eq!(summary.target.version.to_string(), "1.3.0");
+        assert_eq!(
+            versions(&summary.intervening_releases),
+            vec!["1.1.0", "1.2.0"]
+        );
+
+        let summary = updater("1.3.0", UpdateRequest::SpecificVersion("1.0.0".to_owned()))
+            .update_summary()
+            .await
+            .unwrap();
+        assert_eq!(summary.direction, UpdateDirection::Downgrade);
+        assert_eq!(
+            versions(&summary.intervening_releases),
+            vec!["1.1.0", "1.2.0"]
+        );
+
+        let summary = updater("1.3.0", UpdateRequest::Latest)
+            .update_summary()
+            .await
+            .unwrap();
+        assert_eq!(summary.direction, UpdateDirection::Same);
+        assert!(summary.intervening_releases.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_releases_behind() {
+        let behind = updater("1.0.0", UpdateRequest::Latest);
+        // The 1.2.0 beta doesn't count
+        assert_eq!(behind.releases_behind().await.unwrap(), 3);
+        assert_eq!(behind.versions_behind_semver().await.unwrap(), (0, 3, 0));
+
+        let behind = updater("1.2.5", UpdateRequest::Latest);
+        assert_eq!(behind.releases_behind().await.unwrap(), 1);
+        assert_eq!(behind.versions_behind_semver().await.unwrap(), (0, 1, 0));
+
+        let behind = updater("0.9.1", UpdateRequest::Latest);
+        assert_eq!(behind.versions_behind_semver().await.unwrap(), (1, 3, 0));
+
+        let behind = updater("1.3.0", UpdateRequest::Latest);
+        assert_eq!(behind.releases_behind().await.unwrap(), 0);
+        assert_eq!(behind.versions_behind_semver().await.unwrap(), (0, 0, 0));
+    }
+}
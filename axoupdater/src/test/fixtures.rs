@@ -0,0 +1,77 @@
+//! Releases, assets and backends shared by the unit tests
+
+#[cfg(feature = "github_releases")]
+use axoasset::serde_json::{json, Value};
+use futures_util::future::BoxFuture;
+use httpmock::MockServer;
+
+use crate::{Asset, AxoupdateResult, Release, ReleaseBackend, Version};
+
+/// A backend which always lists the same releases, in the given order.
+pub(crate) struct StaticBackend(pub(crate) Vec<Release>);
+
+impl ReleaseBackend for StaticBackend {
+    fn list(&self) -> BoxFuture<'_, AxoupdateResult<Vec<Release>>> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+/// Returns a release of `version`, tagged `v{version}`, with no assets.
+/// It's marked as a prerelease if the version has a prerelease component.
+pub(crate) fn release(version: &str) -> Release {
+    let version = Version::parse(version).unwrap();
+    Release {
+        tag_name: format!("v{version}"),
+        prerelease: !version.pre.is_empty(),
+        version,
+        name: String::new(),
+        url: String::new(),
+        assets: vec![],
+        draft: false,
+        published_at: None,
+        body: None,
+    }
+}
+
+/// Returns a release of `version` with the given assets.
+pub(crate) fn release_with_assets(version: &str, assets: Vec<Asset>) -> Release {
+    Release {
+        assets,
+        ..release(version)
+    }
+}
+
+/// Returns an asset named `name`, which isn't actually downloadable.
+pub(crate) fn asset(name: &str) -> Asset {
+    Asset {
+        url: String::new(),
+        browser_download_url: format!("https://example.com/{name}"),
+        name: name.to_owned(),
+    }
+}
+
+/// Returns an asset named `name`, downloaded from `/{name}` on `server`.
+pub(crate) fn served_asset(server: &MockServer, name: &str) -> Asset {
+    Asset {
+        url: server.url(format!("/{name}")),
+        browser_download_url: server.url(format!("/{name}")),
+        name: name.to_owned(),
+    }
+}
+
+/// Returns the GitHub API's JSON for a release tagged `tag`, with a single
+/// asset named `installer`.
+#[cfg(feature = "github_releases")]
+pub(crate) fn github_release_json(tag: &str, installer: &str) -> Value {
+    json!({
+        "tag_name": tag,
+        "name": tag,
+        "url": "u",
+        "assets": [{
+            "url": "u",
+            "browser_download_url": "bdu",
+            "name": installer,
+        }],
+        "prerelease": false,
+    })
+}
@@ -104,7 +104,7 @@ pub fn perform_runtest(runtest_args: &RuntestArgs) -> PathBuf {
     let app_home = &home.join(".cargo").join("bin");
     let app_path = &app_home.join(basename);
 
-    let config_path = get_config_paths(app_name)
+    let config_path = get_config_paths(app_name, None)
         .unwrap()
         // Accept whichever path comes first; it doesn't matter to us.
         .first()
@@ -2,3 +2,10 @@
 
 /// Test helpers to simplify runtests for custom updaters
 pub mod helpers;
+
+/// Releases and backends shared by the unit tests
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+#[cfg(feature = "test_archives")]
+pub use crate::archive::{extract_archive, ArchiveFormat};
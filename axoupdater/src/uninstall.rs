@@ -0,0 +1,147 @@
+//! Uninstalling apps installed by cargo-dist's installers
+
+use std::env::current_exe;
+
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::{
+    errors::*, find_installed_binary, receipt::get_receipt_path, AxoUpdater, EligibilityResult,
+};
+
+impl AxoUpdater {
+    /// Uninstalls the app described by the loaded install receipt.
+    ///
+    /// If the installation includes an uninstaller, named
+    /// `{app_name}-uninstall`, it's run to perform the uninstall. Otherwise,
+    /// the binaries listed in the receipt, the standalone updater if there
+    /// is one, and the receipt itself are removed.
+    ///
+    /// To avoid removing a copy installed some other way, such as by a
+    /// package manager, this fails with `AxoupdateError::UninstallNotEligible`
+    /// unless the running executable is managed by the receipt.
+    pub fn uninstall(&mut self) -> AxoupdateResult<()> {
+        let Some(app_name) = self.name.clone() else {
+            return Err(AxoupdateError::NoAppNamePassed {});
+        };
+
+        if self.check_eligibility()? != EligibilityResult::Eligible {
+            return Err(AxoupdateError::UninstallNotEligible { app_name });
+        }
+
+        let root = self.installed_binaries_root()?;
+        if let Some(uninstaller) = find_installed_binary(&root, &format!("{app_name}-uninstall")) {
+            return run_uninstaller(&uninstaller);
+        }
+
+        self.remove_installation(&app_name, &root)
+    }
+
+    /// Removes the app's binaries from `root`, along with its receipt.
+    fn remove_installation(&self, app_name: &str, root: &Utf8Path) -> AxoupdateResult<()> {
+        let updater_name = format!("{app_name}-update");
+        let binaries = self
+            .binaries
+            .iter()
+            .chain(std::iter::once(&updater_name))
+            .filter_map(|binary| find_installed_binary(root, binary));
+
+        let ourselves = current_exe().and_then(|path| path.canonicalize()).ok();
+        for binary in binaries {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(%binary, "removing binary");
+
+            let is_ourselves = ourselves.is_some()
+                && binary.canonicalize().ok().as_deref() == ourselves.as_deref();
+            // Windows won't let us delete the running executable directly
+            if cfg!(windows) && is_ourselves {
+                #[cfg(windows)]
+                self_replace::self_delete().map_err(|_| AxoupdateError::CleanupFailed {})?;
+            } else {
+                std::fs::remove_file(&binary)?;
+            }
+        }
+
+        if let Some(receipt_path) = get_receipt_path(app_name, self.config_subdir.as_deref())? {
+            std::fs::remove_file(&receipt_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn run_uninstaller(uninstaller: &Utf8PathBuf) -> AxoupdateResult<()> {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(%uninstaller, "running bundled uninstaller");
+
+    let mut command = Cmd::new(uninstaller, "run the uninstaller");
+    command.check(false);
+    let output = command.output()?;
+
+    if !output.status.success() {
+        return Err(AxoupdateError::UninstallFailed {
+            status: output.status.code(),
+            message: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use camino::Utf8Path;
+    use serial_test::serial;
+
+    use crate::{AxoUpdater, AxoupdateError};
+
+    #[test]
+    fn test_uninstall_requires_eligibility() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        let binary = root.join("bin").join("axolotlsay");
+        std::fs::create_dir_all(binary.parent().unwrap()).unwrap();
+        std::fs::write(&binary, "").unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.install_prefix = Some(root.to_owned());
+        updater.binaries = vec!["axolotlsay".to_owned()];
+
+        // The test executable doesn't live in this prefix, so it isn't
+        // managed by it
+        assert!(matches!(
+            updater.uninstall(),
+            Err(AxoupdateError::UninstallNotEligible { .. })
+        ));
+        assert!(binary.exists());
+    }
+
+    #[test]
+    #[serial] // modifying the global state environment variables
+    fn test_remove_installation() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(tempdir.path()).unwrap();
+        let exe = |name: &str| format!("{name}{}", std::env::consts::EXE_SUFFIX);
+        let bin = root.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        for name in ["axolotlsay", "axolotlsay-update", "unrelated"] {
+            std::fs::write(bin.join(exe(name)), "").unwrap();
+        }
+        let config = tempfile::TempDir::new().unwrap();
+        let receipt = config.path().join("axolotlsay-receipt.json");
+        std::fs::write(&receipt, "{}").unwrap();
+
+        let mut updater = AxoUpdater::new_for("axolotlsay");
+        updater.binaries = vec!["axolotlsay".to_owned()];
+
+        std::env::set_var("AXOUPDATER_CONFIG_PATH", config.path());
+        let result = updater.remove_installation("axolotlsay", root);
+        std::env::remove_var("AXOUPDATER_CONFIG_PATH");
+
+        result.unwrap();
+        assert!(!bin.join(exe("axolotlsay")).exists());
+        assert!(!bin.join(exe("axolotlsay-update")).exists());
+        assert!(bin.join(exe("unrelated")).exists());
+        assert!(!receipt.exists());
+    }
+}
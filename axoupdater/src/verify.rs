@@ -0,0 +1,490 @@
+//! Verifying downloaded installers
+
+use std::{fs::File, io::Read};
+
+use axoasset::{reqwest, serde_json};
+use camino::Utf8Path;
+use sha2::{Digest, Sha256};
+
+use crate::{errors::*, Asset, AxoUpdater, Release};
+
+/// A signature scheme used to verify installers before they're run.
+/// Implement this for the signing tool used for your releases (for
+/// example, minisign or GPG) and pass it to
+/// `AxoUpdater::set_signature_verifier`.
+pub trait SignatureVerifier: Send + Sync {
+    /// Verifies `installer_bytes` against `signature_bytes`. Returning an
+    /// error prevents the installer from running; the message is surfaced
+    /// to the user.
+    fn verify(&self, installer_bytes: &[u8], signature_bytes: &[u8]) -> Result<(), String>;
+
+    /// Verifies the installer at `installer_path` against
+    /// `signature_bytes`. This is what's called on downloaded installers;
+    /// by default, it reads the installer into memory and calls `verify`.
+    /// Override it to check large installers a piece at a time instead.
+    fn verify_file(&self, installer_path: &Utf8Path, signature_bytes: &[u8]) -> Result<(), String> {
+        let installer_bytes = std::fs::read(installer_path)
+            .map_err(|e| format!("couldn't read {installer_path}: {e}"))?;
+        self.verify(&installer_bytes, signature_bytes)
+    }
+}
+
+/// Extensions used by signature assets, in order of preference.
+const SIGNATURE_EXTENSIONS: &[&str] = &["minisig", "asc"];
+
+/// The name of the manifest cargo-dist uploads alongside each release
+const DIST_MANIFEST_NAME: &str = "dist-manifest.json";
+
+/// The size, in bytes, below which downloaded installers are rejected by
+/// default. Real installers are several kilobytes; anything this small is
+/// most likely the result of a failed upload.
+pub(crate) const DEFAULT_MIN_INSTALLER_SIZE: usize = 256;
+
+/// How much of an installer is read to sanity-check its contents
+const INSTALLER_HEAD_SIZE: u64 = 8192;
+
+impl AxoUpdater {
+    /// Configures a verifier to check the signature of downloaded installers
+    /// before running them. The signature is read from an asset in the same
+    /// release named after the installer with a `.minisig` or `.asc`
+    /// extension; if neither is present, the update fails.
+    /// Installers configured via `configure_installer_path` aren't verified.
+    pub fn set_signature_verifier(
+        &mut self,
+        verifier: Box<dyn SignatureVerifier>,
+    ) -> &mut AxoUpdater {
+        self.signature_verifier = Some(verifier);
+
+        self
+    }
+
+    /// Verifies the installer downloaded to `installer_path` using the
+    /// configured signature verifier, if any.
+    pub(crate) async fn verify_installer_signature(
+        &self,
+        client: &reqwest::Client,
+        release: &Release,
+        installer: &Asset,
+        installer_path: &Utf8Path,
+    ) -> AxoupdateResult<()> {
+        let Some(verifier) = &self.signature_verifier else {
+            return Ok(());
+        };
+
+        // Asset names are matched exactly, rather than as patterns, so that
+        // an installer name containing `*` or `?` can't pick up another
+        // installer's signature
+        let Some(signature_asset) = SIGNATURE_EXTENSIONS.iter().find_map(|extension| {
+            let name = format!("{}.{extension}", installer.name);
+            release.assets.iter().find(|asset| asset.name == name)
+        }) else {
+            return Err(AxoupdateError::SignatureVerificationFailed {
+                message: format!("no signature was found for {}", installer.name),
+            });
+        };
+
+        let signature = self
+            .asset_request(client, signature_asset)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        verifier
+            .verify_file(installer_path, &signature)
+            .map_err(|message| AxoupdateError::SignatureVerificationFailed { message })
+    }
+
+    /// Configures whether downloaded installers should be checked against
+    /// the SHA256 checksums recorded in the release's `dist-manifest.json`.
+    /// If enabled and the manifest or the installer's checksum is missing,
+    /// the update fails.
+    /// Installers configured via `configure_installer_path` aren't verified.
+    pub fn verify_via_dist_manifest(&mut self, setting: bool) -> &mut AxoUpdater {
+        self.verify_dist_manifest = setting;
+
+        self
+    }
+
+    /// Configures the size, in bytes, below which downloaded installers are
+    /// rejected with `AxoupdateError::InvalidInstaller` instead of being
+    /// run. Releases have occasionally been published with empty or
+    /// truncated installers after a failed upload; running one of those
+    /// does nothing, so the update would appear to succeed without
+    /// installing anything. Defaults to 256 bytes; set it to 0 to only
+    /// reject empty installers.
+    pub fn set_min_installer_size(&mut self, bytes: usize) -> &mut AxoUpdater {
+        self.min_installer_size = bytes;
+
+        self
+    }
+
+    /// Verifies the checksum of the installer downloaded to
+    /// `installer_path` against the release's dist manifest, if enabled.
+    pub(crate) async fn verify_installer_checksum(
+        &self,
+        client: &reqwest::Client,
+        release: &Release,
+        installer: &Asset,
+        installer_path: &Utf8Path,
+    ) -> AxoupdateResult<()> {
+        if !self.verify_dist_manifest {
+            return Ok(());
+        }
+
+        let Some(manifest_asset) = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == DIST_MANIFEST_NAME)
+        else {
+            return Err(AxoupdateError::ChecksumVerificationFailed {
+                message: format!("the release has no {DIST_MANIFEST_NAME}"),
+            });
+        };
+
+        // The manifest's schema changes between cargo-dist versions, so we
+        // only read the one field we need rather than deserializing it fully.
+        let manifest: serde_json::Value = self
+            .asset_request(client, manifest_asset)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let Some(expected) = manifest
+            .pointer(&format!(
+                "/artifacts/{}/checksums/sha256",
+                escape_json_pointer(&installer.name)
+            ))
+            .and_then(|checksum| checksum.as_str())
+        else {
+            return Err(AxoupdateError::ChecksumVerificationFailed {
+                message: format!(
+                    "{DIST_MANIFEST_NAME} has no checksum for {}",
+                    installer.name
+                ),
+            });
+        };
+
+        let actual = sha256_file_hex(installer_path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(AxoupdateError::ChecksumVerificationFailed {
+                message: format!("expected {expected}, but the installer's checksum was {actual}"),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Sanity-checks the installer downloaded to `path` before it's run; see
+/// `check_installer_contents`. Only the start of the installer is read.
+pub(crate) fn check_installer_file(
+    path: &Utf8Path,
+    powershell: bool,
+    min_size: usize,
+) -> AxoupdateResult<()> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut head = vec![];
+    file.by_ref()
+        .take(INSTALLER_HEAD_SIZE)
+        .read_to_end(&mut head)?;
+
+    check_installer_contents(&head, size, powershell, min_size)
+}
+
+/// Sanity-checks a downloaded installer before it's run, so that an empty
+/// body or an HTML error page produces a clear error instead of a confusing
+/// shell or PowerShell syntax error. `head` is the start of the installer,
+/// and `size` is its full size. Installers smaller than `min_size` bytes
+/// are rejected too, since running a truncated one would silently do
+/// nothing.
+pub(crate) fn check_installer_contents(
+    head: &[u8],
+    size: u64,
+    powershell: bool,
+    min_size: usize,
+) -> AxoupdateResult<()> {
+    let text = installer_text(head);
+    let contents = text.trim_start_matches('\u{feff}');
+    let Some(first_line) = contents.lines().find(|line| !line.trim().is_empty()) else {
+        return Err(AxoupdateError::InvalidInstaller {
+            reason: "it's empty".to_owned(),
+        });
+    };
+    if size < min_size as u64 {
+        return Err(AxoupdateError::InvalidInstaller {
+            reason: format!("it's only {size} bytes, which is too small to be an installer"),
+        });
+    }
+    let first_line = first_line.trim().to_ascii_lowercase();
+
+    if first_line.starts_with("<!doctype") || first_line.starts_with("<html") {
+        return Err(AxoupdateError::InvalidInstaller {
+            reason: "it's an HTML page rather than a script".to_owned(),
+        });
+    }
+    if !powershell && !contents.starts_with("#!") {
+        return Err(AxoupdateError::InvalidInstaller {
+            reason: "it doesn't start with a shebang (#!)".to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Decodes an installer's contents as text so that they can be inspected.
+/// PowerShell scripts may be saved as UTF-16 with a byte order mark;
+/// anything else is treated as UTF-8.
+fn installer_text(contents: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+
+    match contents {
+        [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(contents).into_owned(),
+    }
+}
+
+/// Escapes a key for use as a single segment of a JSON pointer.
+fn escape_json_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Returns the lowercase hex-encoded SHA256 checksum of `bytes`.
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+/// Returns the lowercase hex-encoded SHA256 checksum of the file at `path`,
+/// reading it a piece at a time.
+fn sha256_file_hex(path: &Utf8Path) -> AxoupdateResult<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        check_installer_contents, check_installer_file, SignatureVerifier,
+        DEFAULT_MIN_INSTALLER_SIZE,
+    };
+    use crate::test::fixtures::{release_with_assets, served_asset};
+    use crate::{errors::AxoupdateError, AxoUpdater};
+    use axoasset::reqwest;
+    use axoasset::serde_json::json;
+    use camino::Utf8PathBuf;
+    use httpmock::prelude::*;
+    use serial_test::serial;
+
+    struct ExpectSignature(&'static [u8]);
+
+    impl SignatureVerifier for ExpectSignature {
+        fn verify(&self, _installer_bytes: &[u8], signature_bytes: &[u8]) -> Result<(), String> {
+            if signature_bytes == self.0 {
+                Ok(())
+            } else {
+                Err("bad signature".to_owned())
+            }
+        }
+    }
+
+    /// Writes `contents` to `name` in `dir`, as if it had been downloaded
+    /// there.
+    fn downloaded(dir: &tempfile::TempDir, name: &str, contents: &[u8]) -> Utf8PathBuf {
+        let path = Utf8PathBuf::from_path_buf(dir.path().join(name)).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_check_installer_contents() {
+        let check = |contents: &[u8], powershell, min_size| {
+            check_installer_contents(contents, contents.len() as u64, powershell, min_size)
+        };
+
+        assert!(check(b"#!/bin/sh\necho hi\n", false, 0).is_ok());
+        assert!(check(b"\n# installer\nWrite-Host hi\n", true, 0).is_ok());
+
+        // PowerShell scripts saved as UTF-16 with a byte order mark
+        let utf16: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(
+                "# installer\r\nWrite-Host hi\r\n"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        assert!(check(&utf16, true, 0).is_ok());
+        let utf16_html: Vec<u8> = [0xfe, 0xff]
+            .into_iter()
+            .chain("<html></html>".encode_utf16().flat_map(u16::to_be_bytes))
+            .collect();
+        assert!(check(&utf16_html, true, 0).is_err());
+
+        for (contents, powershell) in [
+            ("", false),
+            ("  \n", true),
+            ("<!DOCTYPE html>\n<html></html>", false),
+            ("\n<html><body>rate limited</body></html>", true),
+            ("echo hi\n", false),
+        ] {
+            assert!(matches!(
+                check(contents.as_bytes(), powershell, 0),
+                Err(AxoupdateError::InvalidInstaller { .. })
+            ));
+        }
+
+        // Truncated installers are rejected, however valid they look
+        let tiny = b"#!/bin/sh\n";
+        assert!(matches!(
+            check(tiny, false, DEFAULT_MIN_INSTALLER_SIZE),
+            Err(AxoupdateError::InvalidInstaller { .. })
+        ));
+        let full = format!("#!/bin/sh\n{}", "# padding\n".repeat(30));
+        assert!(check(full.as_bytes(), false, DEFAULT_MIN_INSTALLER_SIZE).is_ok());
+    }
+
+    #[test]
+    fn test_check_installer_file() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        // Only the start of the installer is read, but its full size is
+        // what's compared against the minimum
+        let padded = format!("#!/bin/sh\n{}", "# padding\n".repeat(2000));
+        let installer = downloaded(&tempdir, "installer.sh", padded.as_bytes());
+        assert!(check_installer_file(&installer, false, 20_000).is_ok());
+        assert!(matches!(
+            check_installer_file(&installer, false, 30_000),
+            Err(AxoupdateError::InvalidInstaller { .. })
+        ));
+
+        let page = downloaded(&tempdir, "page.sh", b"<html></html>");
+        assert!(check_installer_file(&page, false, 0).is_err());
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_verify_installer_signature() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/app-installer.sh.minisig");
+                then.status(200).body("good");
+            })
+            .await;
+
+        let installer = served_asset(&server, "app-installer.sh");
+        let release = release_with_assets(
+            "1.0.0",
+            vec![
+                installer.clone(),
+                served_asset(&server, "app-installer.sh.minisig"),
+            ],
+        );
+        let client = reqwest::Client::new();
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let script = downloaded(&tempdir, "installer.sh", b"#!/bin/sh");
+
+        let mut updater = AxoUpdater::new();
+        updater.set_signature_verifier(Box::new(ExpectSignature(b"good")));
+        assert!(updater
+            .verify_installer_signature(&client, &release, &installer, &script)
+            .await
+            .is_ok());
+
+        // The signature's name isn't treated as a pattern
+        let wildcard = served_asset(&server, "app-*.sh");
+        assert!(matches!(
+            updater
+                .verify_installer_signature(&client, &release, &wildcard, &script)
+                .await,
+            Err(AxoupdateError::SignatureVerificationFailed { .. })
+        ));
+
+        updater.set_signature_verifier(Box::new(ExpectSignature(b"other")));
+        assert!(matches!(
+            updater
+                .verify_installer_signature(&client, &release, &installer, &script)
+                .await,
+            Err(AxoupdateError::SignatureVerificationFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[serial] // reads proxy settings from the environment
+    async fn test_verify_installer_checksum() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/dist-manifest.json");
+                then.status(200).json_body(json!({
+                    "dist_version": "0.28.0",
+                    "some_future_field": [1, 2, 3],
+                    "artifacts": {
+                        "app-installer.sh": {
+                            "name": "app-installer.sh",
+                            "kind": "installer",
+                            "checksums": {
+                                "sha256": "3af71adb278ad4af33c144b78fa1ae708da03b773d98324ae991a7daedb53ca2"
+                            }
+                        }
+                    }
+                }));
+            })
+            .await;
+
+        let installer = served_asset(&server, "app-installer.sh");
+        let mut release = release_with_assets(
+            "1.0.0",
+            vec![
+                installer.clone(),
+                served_asset(&server, "dist-manifest.json"),
+            ],
+        );
+        let client = reqwest::Client::new();
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let script = downloaded(&tempdir, "installer.sh", b"#!/bin/sh");
+        let tampered = downloaded(&tempdir, "tampered.sh", b"tampered");
+
+        let mut updater = AxoUpdater::new();
+        // Nothing is checked unless verification is enabled
+        assert!(updater
+            .verify_installer_checksum(&client, &release, &installer, &tampered)
+            .await
+            .is_ok());
+
+        updater.verify_via_dist_manifest(true);
+        assert!(updater
+            .verify_installer_checksum(&client, &release, &installer, &script)
+            .await
+            .is_ok());
+        assert!(matches!(
+            updater
+                .verify_installer_checksum(&client, &release, &installer, &tampered)
+                .await,
+            Err(AxoupdateError::ChecksumVerificationFailed { .. })
+        ));
+
+        release.assets.pop();
+        assert!(matches!(
+            updater
+                .verify_installer_checksum(&client, &release, &installer, &script)
+                .await,
+            Err(AxoupdateError::ChecksumVerificationFailed { .. })
+        ));
+    }
+}